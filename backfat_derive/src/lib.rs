@@ -0,0 +1,158 @@
+//! `#[derive(ListRow)]` for backfat. Each named field is a column of a ListBox, set up with
+//! `#[list_row(...)]`:
+//!
+//! * `header = "Invoice Amount"` the header text, the field name in title case when left out
+//! * `width = 25.0` percent of the range the table is placed in, fields without one share what's left of it
+//! * `currency = 2` or `number = 2` the column's TypeOfItem and its precision, text otherwise
+//! * `align = "RightCenter"` a TextAlignment, like `"LeftJustifyTop(0.05)"`
+//! * `skip` leaves the field out
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Field, Fields, Ident, Lit, Meta, NestedMeta};
+
+//what a field's list_row attribute asks for.
+struct Column {
+    ident: Ident,
+    header: String,
+    width: Option<f64>,
+    type_of_item: TokenStream2,
+    alignment: TokenStream2,
+}
+
+#[proc_macro_derive(ListRow, attributes(list_row))]
+pub fn derive_list_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(input, "ListRow needs a struct with named fields")),
+        },
+        _ => return Err(Error::new_spanned(input, "ListRow can only be derived for structs")),
+    };
+    let mut columns = Vec::new();
+    for field in fields {
+        if let Some(column) = parse_column(field)? {
+            columns.push(column);
+        }
+    }
+    let given_width: f64 = columns.iter().filter_map(|x| x.width).sum();
+    let unsized_count = columns.iter().filter(|x| x.width.is_none()).count();
+    let shared_width = if unsized_count > 0 {
+        ((100.0 - given_width) / unsized_count as f64).max(0.0)
+    } else {
+        0.0
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let idents = columns.iter().map(|x| &x.ident);
+    let headers = columns.iter().map(|x| &x.header);
+    let widths = columns.iter().map(|x| x.width.unwrap_or(shared_width));
+    let types = columns.iter().map(|x| &x.type_of_item);
+    let alignments = columns.iter().map(|x| &x.alignment);
+    Ok(quote! {
+        impl #impl_generics ::backfat::container_objects::list_box::ListRow for #name #ty_generics #where_clause {
+            fn to_row(&self) -> ::backfat::container_objects::list_box::RowData {
+                let cells: ::std::vec::Vec<::backfat::format_objects::cell_value::CellValue> = vec![
+                    #(::backfat::format_objects::cell_value::CellValue::from(self.#idents.clone())),*
+                ];
+                ::backfat::container_objects::list_box::RowData::new(
+                    cells,
+                    ::backfat::container_objects::list_box::RowDataTypes::Normal,
+                )
+            }
+            fn header() -> ::backfat::container_objects::list_box::RowData {
+                let headers: ::std::vec::Vec<&str> = vec![#(#headers),*];
+                ::backfat::container_objects::list_box::RowData::new(
+                    headers,
+                    ::backfat::container_objects::list_box::RowDataTypes::Normal,
+                )
+            }
+            fn column_widths(range_percent: f64) -> ::std::vec::Vec<f64> {
+                vec![#(#widths * range_percent / 100.0),*]
+            }
+            fn column_types() -> ::std::vec::Vec<::backfat::container_objects::list_box::TypeOfItem> {
+                vec![#(#types),*]
+            }
+            fn column_alignments() -> ::std::vec::Vec<::backfat::container_objects::text_box::TextAlignment> {
+                vec![#(#alignments),*]
+            }
+        }
+    })
+}
+
+//None when the field is skipped.
+fn parse_column(field: &Field) -> syn::Result<Option<Column>> {
+    let ident = field.ident.clone().unwrap();
+    let mut column = Column {
+        header: title_case(&ident.to_string()),
+        ident,
+        width: None,
+        type_of_item: quote!(::backfat::container_objects::list_box::TypeOfItem::String),
+        alignment: quote!(::backfat::container_objects::text_box::TextAlignment::LeftBottom),
+    };
+    for attr in field.attrs.iter().filter(|x| x.path.is_ident("list_row")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new_spanned(meta, "expected #[list_row(...)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => return Ok(None),
+                NestedMeta::Meta(Meta::NameValue(pair)) => {
+                    let key = pair.path.get_ident().map(|x| x.to_string()).unwrap_or_default();
+                    match (key.as_str(), &pair.lit) {
+                        ("header", Lit::Str(text)) => column.header = text.value(),
+                        ("width", Lit::Float(width)) => column.width = Some(width.base10_parse()?),
+                        ("width", Lit::Int(width)) => column.width = Some(width.base10_parse()?),
+                        ("currency", Lit::Int(precision)) => {
+                            let precision: usize = precision.base10_parse()?;
+                            column.type_of_item = quote!(
+                                ::backfat::container_objects::list_box::TypeOfItem::Currency(#precision)
+                            );
+                        }
+                        ("number", Lit::Int(precision)) => {
+                            let precision: usize = precision.base10_parse()?;
+                            column.type_of_item = quote!(
+                                ::backfat::container_objects::list_box::TypeOfItem::Number(#precision)
+                            );
+                        }
+                        ("align", Lit::Str(alignment)) => {
+                            let alignment: syn::Expr = alignment.parse()?;
+                            column.alignment =
+                                quote!(::backfat::container_objects::text_box::TextAlignment::#alignment);
+                        }
+                        _ => return Err(Error::new_spanned(pair, "unknown list_row setting")),
+                    }
+                }
+                nested => return Err(Error::new_spanned(nested, "unknown list_row setting")),
+            }
+        }
+    }
+    Ok(Some(column))
+}
+
+//invoice_amount becomes Invoice Amount.
+fn title_case(name: &str) -> String {
+    name.split('_')
+        .filter(|x| !x.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
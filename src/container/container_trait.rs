@@ -1,3 +1,5 @@
+use crate::container::layout_context::LayoutContext;
+use crate::container::page_size_info::PageSizeInfo;
 use crate::container::placement_info::PlacementInfo;
 use crate::container::rectangle::Border;
 use std::cell::RefCell;
@@ -9,6 +11,11 @@ pub trait DrawInfoReq {
     fn increment_page_buffer(&mut self, page_number: usize);
     fn page_array_size(&self) -> usize;
     fn insert_into_page(&mut self, page_num: usize, operation: Operation);
+    fn page_operation_len(&self, page_num: usize) -> usize;
+    ///Drops the operations on the page past len, used to roll back a placement.
+    fn truncate_page(&mut self, page_num: usize, len: usize);
+    ///Drops every page from page_cnt on.
+    fn truncate_pages(&mut self, page_cnt: usize);
 }
 
 pub trait ContainerTrait {
@@ -19,4 +26,138 @@ pub trait ContainerTrait {
         borders: &Option<RefCell<Vec<Border>>>,
     ) -> Option<PlacementInfo>;
     fn get_group(&self) -> Option<usize>;
+    ///Height in pixels the container needs to show all of its content at the given width.
+    ///Containers that can't measure themselves return None.
+    fn get_content_height(&self, _width_pixels: f64, _page_size_info: &PageSizeInfo) -> Option<f64> {
+        None
+    }
+    ///Called before each layout pass of a deferred build with what the last pass found.
+    fn on_layout(&mut self, _layout: &LayoutContext) {}
+    ///False for containers that use themselves up drawing, like ones reading from a stream.
+    ///A deferred build only reserves their content height in the layout passes and draws them once.
+    fn can_redraw(&self) -> bool {
+        true
+    }
+}
+
+///Object safe counterpart of ContainerTrait so different containers can live together as
+///`Box<dyn Container>`. Every ContainerTrait gets this for free.
+pub trait Container {
+    fn draw(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_info: &mut dyn DrawInfoReq,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo>;
+    fn group(&self) -> Option<usize>;
+    fn content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64>;
+    fn layout(&mut self, layout: &LayoutContext);
+    fn redrawable(&self) -> bool;
+}
+
+impl DrawInfoReq for &mut dyn DrawInfoReq {
+    fn increment_page_buffer(&mut self, page_number: usize) {
+        (**self).increment_page_buffer(page_number)
+    }
+
+    fn page_array_size(&self) -> usize {
+        (**self).page_array_size()
+    }
+
+    fn insert_into_page(&mut self, page_num: usize, operation: Operation) {
+        (**self).insert_into_page(page_num, operation)
+    }
+
+    fn page_operation_len(&self, page_num: usize) -> usize {
+        (**self).page_operation_len(page_num)
+    }
+
+    fn truncate_page(&mut self, page_num: usize, len: usize) {
+        (**self).truncate_page(page_num, len)
+    }
+
+    fn truncate_pages(&mut self, page_cnt: usize) {
+        (**self).truncate_pages(page_cnt)
+    }
+}
+
+impl<C: ContainerTrait> Container for C {
+    fn draw(
+        &mut self,
+        placement_info: PlacementInfo,
+        mut draw_info: &mut dyn DrawInfoReq,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        self.on_draw(placement_info, &mut draw_info, borders)
+    }
+
+    fn group(&self) -> Option<usize> {
+        self.get_group()
+    }
+
+    fn content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64> {
+        self.get_content_height(width_pixels, page_size_info)
+    }
+
+    fn layout(&mut self, layout: &LayoutContext) {
+        self.on_layout(layout)
+    }
+
+    fn redrawable(&self) -> bool {
+        self.can_redraw()
+    }
+}
+
+impl<'a> ContainerTrait for dyn Container + 'a {
+    fn on_draw<T: DrawInfoReq>(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_info: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        self.draw(placement_info, draw_info, borders)
+    }
+
+    fn get_group(&self) -> Option<usize> {
+        self.group()
+    }
+
+    fn get_content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64> {
+        self.content_height(width_pixels, page_size_info)
+    }
+
+    fn on_layout(&mut self, layout: &LayoutContext) {
+        self.layout(layout)
+    }
+
+    fn can_redraw(&self) -> bool {
+        self.redrawable()
+    }
+}
+
+impl<'a> ContainerTrait for Box<dyn Container + 'a> {
+    fn on_draw<T: DrawInfoReq>(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_info: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        self.as_mut().draw(placement_info, draw_info, borders)
+    }
+
+    fn get_group(&self) -> Option<usize> {
+        self.as_ref().group()
+    }
+
+    fn get_content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64> {
+        self.as_ref().content_height(width_pixels, page_size_info)
+    }
+
+    fn on_layout(&mut self, layout: &LayoutContext) {
+        self.as_mut().layout(layout)
+    }
+
+    fn can_redraw(&self) -> bool {
+        self.as_ref().redrawable()
+    }
 }
@@ -0,0 +1,23 @@
+use std::collections::BTreeMap;
+
+///What the previous layout pass found out about the document, handed to every queued
+///container before the next pass so it can depend on things that come later.
+#[derive(Clone, Default, PartialEq)]
+pub struct LayoutContext {
+    pub pass: usize,
+    pub total_pages: usize,
+    pub page_numbers: Vec<usize>,
+    pub named_pages: BTreeMap<String, usize>,
+}
+
+impl LayoutContext {
+    pub fn get_named_page(&self, name: &str) -> Option<usize> {
+        self.named_pages.get(name).copied()
+    }
+    ///Same layout result regardless of which pass produced it.
+    pub fn is_settled_with(&self, other: &LayoutContext) -> bool {
+        self.total_pages == other.total_pages
+            && self.page_numbers == other.page_numbers
+            && self.named_pages == other.named_pages
+    }
+}
@@ -1,36 +1,88 @@
-use crate::container::container_trait::{ContainerTrait, DrawInfoReq};
+use crate::container::container_trait::{Container, ContainerTrait, DrawInfoReq};
+use crate::container::layout_context::LayoutContext;
 use crate::container::page_master::{PageMargins, PageMaster};
 use crate::container::page_size_info::PageSizeInfo;
-use crate::container::placement_info::{PlacementInfo, PlacementOptions};
+use crate::container::placement_info::{Anchor, PlacementInfo, PlacementOptions, RelativePlacement};
 use crate::container::rectangle::{Border, Rectangle};
+use crate::container::span::Span;
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use lopdf::content::Operation;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
 use std::fmt::{Display, Formatter};
-use std::ops::Range;
 use std::sync::{Arc, Mutex};
 
+///Most passes a deferred build will run before drawing, even if the layout keeps changing.
+const MAX_LAYOUT_PASSES: usize = 4;
+
+#[derive(Default)]
 pub struct LocationInfo {
-    pub range: Range<usize>,
+    pub range: Span,
+    ///Fixed height in pixels, None uses the container's content height.
+    pub height: Option<f64>,
+    pub start_new_page: bool,
+    ///Moves this container to the next page when its content height doesn't fit on this one.
+    pub keep_together: bool,
+    ///Moves this container to the next page when the following one would not share its page.
+    pub keep_with_next: bool,
+    ///Name the page number is reported under in the LayoutContext.
+    pub name: Option<String>,
+}
+impl LocationInfo {
+    pub fn new<S: Into<Span>>(range: S, height: Option<f64>) -> Self {
+        Self {
+            range: range.into(),
+            height,
+            ..Default::default()
+        }
+    }
+}
+
+//stands in for the real draw target during layout passes, only keeps track of the pages.
+#[derive(Default)]
+struct LayoutDrawInfo {
+    page_cnt: usize,
+}
+impl DrawInfoReq for LayoutDrawInfo {
+    fn increment_page_buffer(&mut self, page_number: usize) {
+        if page_number >= self.page_cnt {
+            self.page_cnt = page_number + 1;
+        }
+    }
+
+    fn page_array_size(&self) -> usize {
+        self.page_cnt
+    }
+
+    fn insert_into_page(&mut self, _page_num: usize, _operation: Operation) {}
+
+    fn page_operation_len(&self, _page_num: usize) -> usize {
+        0
+    }
+
+    fn truncate_page(&mut self, _page_num: usize, _len: usize) {}
+
+    fn truncate_pages(&mut self, page_cnt: usize) {
+        self.page_cnt = self.page_cnt.min(page_cnt);
+    }
+}
+
+///Column state and output sizes saved by Manager::checkpoint so a layout can be tried and undone.
+pub struct Checkpoint {
+    page_master: PageMaster,
+    page_operation_lens: Vec<usize>,
+    border_cnt: usize,
 }
 
 pub struct Manager {
-    //   container: VecDeque<(LocationInfo, Box<dyn ContainerTrait<T>>)>,
+    container: VecDeque<(LocationInfo, Box<dyn Container>)>,
     page_master: Arc<Mutex<PageMaster>>,
 }
 
 impl Manager {
+    ///Size, margins and columns of the page the flow is currently on.
     pub fn get_page_info(&self) -> PageSizeInfo {
-        let info = self.page_master.lock().unwrap();
-        let page_info = info.get_page_info();
-        let margin_info = info.get_margins_pixels();
-        PageSizeInfo::new(
-            page_info.0,
-            page_info.1,
-            page_info.2,
-            margin_info.top_margin_pixels,
-            margin_info.bottom_margin_pixels,
-        )
+        self.page_master.lock().unwrap().get_current_page_size_info()
     }
     pub fn get_groups(&self) -> BTreeMap<usize, Vec<Rectangle>> {
         self.page_master.lock().unwrap().get_group_borders()
@@ -43,7 +95,7 @@ impl Manager {
         bottom_margin_inch: f64,
     ) -> Self {
         Self {
-            //    container: VecDeque::new(),
+            container: VecDeque::new(),
             page_master: Arc::new(Mutex::new(PageMaster::new(
                 width_inches * dpi,
                 height_inches * dpi,
@@ -53,6 +105,21 @@ impl Manager {
             ))),
         }
     }
+    ///Size and margins of a page, following any section breaks.
+    pub fn get_page_size_info(&self, page_number: usize) -> PageSizeInfo {
+        self.page_master.lock().unwrap().get_page_size_info(page_number)
+    }
+    ///Moves the flow to a new page that uses the given size, margins and columns, as do the pages after it.
+    ///Returns the new page's number.
+    pub fn section_break(&mut self, page_size_info: PageSizeInfo) -> usize {
+        self.page_master.lock().unwrap().section_break(page_size_info)
+    }
+    ///Inside and outside margins for duplex printing, they swap sides on every other page.
+    pub fn set_mirrored_margins(&mut self, inside_margin_inch: f64, outside_margin_inch: f64) {
+        let mut page_master = self.page_master.lock().unwrap();
+        let (_, _, dpi) = page_master.get_page_info();
+        page_master.set_mirrored_margins(inside_margin_inch * dpi, outside_margin_inch * dpi);
+    }
     pub fn get_page_pixel_dims(&self) -> (f64, f64, f64) {
         self.page_master.lock().unwrap().get_page_info()
     }
@@ -60,12 +127,66 @@ impl Manager {
         self.page_master.lock().unwrap().get_margins_pixels()
     }
 
-    pub fn get_placement_handle(
+    pub fn get_placement_handle<S: Into<Span>>(
         &mut self,
-        range: Range<usize>,
+        range: S,
         start_new_page: bool,
     ) -> CurrentPlacement {
-        CurrentPlacement::new(&self.page_master, Some(range), start_new_page)
+        CurrentPlacement::new(&self.page_master, Some(range.into()), start_new_page)
+    }
+    pub fn get_placement_handle_with_options<S: Into<Span>>(
+        &mut self,
+        range: S,
+        options: PlacementOptions,
+    ) -> CurrentPlacement {
+        CurrentPlacement::new_with_options(&self.page_master, Some(range.into()), options)
+    }
+    ///Starts a block of placements that should stay on one page. If the block's total height
+    ///doesn't fit under what is already in the range, the range moves to the next page.
+    ///Returns true when it had to move. A single container is kept together by
+    ///PlacementOptions::keep_together, which measures it.
+    pub fn keep_together<S: Into<Span>>(&mut self, range: S, height_pixels: f64) -> bool {
+        let mut page_master = self.page_master.lock().unwrap();
+        let range = page_master.get_percent_range(range.into());
+        let placement_info =
+            page_master.get_next_top_position(PlacementOptions::default(), range.clone());
+        if !placement_info.is_new_page && placement_info.draw_height_left_on_page < height_pixels {
+            page_master.move_to_next_page(range);
+            true
+        } else {
+            false
+        }
+    }
+    ///Moves every column to the top of the page after the lowest one. Returns the new page's number.
+    pub fn page_break(&mut self) -> usize {
+        self.break_to_page(|_| true, false)
+    }
+    ///Page break that lands on an odd printed page, the first page being 1, leaving a blank page if needed.
+    ///Nothing happens when the flow is still at the top of an odd page.
+    pub fn break_to_odd_page(&mut self) -> usize {
+        self.break_to_page(|page_number| page_number % 2 == 0, true)
+    }
+    ///Page break that lands on an even printed page, leaving a blank page if needed.
+    ///Nothing happens when the flow is still at the top of an even page.
+    pub fn break_to_even_page(&mut self) -> usize {
+        self.break_to_page(|page_number| page_number % 2 == 1, true)
+    }
+    //first page after the current one that is_wanted accepts, page numbers start at 0. With
+    //keep_empty_page the current page is used if nothing is on it yet and is_wanted accepts it.
+    fn break_to_page<F: Fn(usize) -> bool>(&mut self, is_wanted: F, keep_empty_page: bool) -> usize {
+        let mut page_master = self.page_master.lock().unwrap();
+        let (largest_page, largest_height) = page_master.get_largest_for_range(0.0..100.0);
+        let is_empty = largest_height <= page_master.get_page_size_info(largest_page).top_margin;
+        if keep_empty_page && is_empty && is_wanted(largest_page) {
+            page_master.move_all_to_page(largest_page);
+            return largest_page;
+        }
+        let mut page_number = largest_page + 1;
+        while !is_wanted(page_number) {
+            page_number += 1;
+        }
+        page_master.move_all_to_page(page_number);
+        page_number
     }
     /*pub fn alter_placement_by_options(&mut self, info:PlacementInfo, opts: PlacementOptions ) -> PlacementInfo {
 
@@ -74,7 +195,7 @@ impl Manager {
     pub fn get_page_cnt(&self) -> usize {
         self.page_master.lock().unwrap().get_page_cnt()
     }
-    pub fn place_now<T: DrawInfoReq, F: ContainerTrait>(&mut self, height_pixels: f64, range: Range<usize>, draw_info: &mut T, f: &mut F, border: &Option<RefCell<Vec<Border>>>) {
+    pub fn place_now<S: Into<Span>, T: DrawInfoReq, F: ContainerTrait + ?Sized>(&mut self, height_pixels: f64, range: S, draw_info: &mut T, f: &mut F, border: &Option<RefCell<Vec<Border>>>) {
         let mut placement_handle = self.get_placement_handle( range, false );
         placement_handle.set_pixel_height(height_pixels);
         let draw_rec = placement_handle.placement_info.clone();
@@ -92,6 +213,262 @@ impl Manager {
             .unwrap()
             .update_placement(&placement_handle.placement_info);
     }
+    ///Draws the container at the anchor, reserving the range down to its bottom. When it doesn't
+    ///fit on the current page it goes on the next one and false is returned.
+    pub fn place_anchored<S: Into<Span>, T: DrawInfoReq, F: ContainerTrait + ?Sized>(
+        &mut self,
+        height_pixels: f64,
+        range: S,
+        anchor: Anchor,
+        draw_info: &mut T,
+        f: &mut F,
+        border: &Option<RefCell<Vec<Border>>>,
+    ) -> bool {
+        let (placement_info, fits) = {
+            let mut page_master = self.page_master.lock().unwrap();
+            let range = page_master.get_percent_range(range.into());
+            page_master.get_anchored_position(range, height_pixels, anchor)
+        };
+        if placement_info.page_number >= draw_info.page_array_size() {
+            draw_info.increment_page_buffer(placement_info.page_number);
+        }
+        let last_placement = f
+            .on_draw(placement_info.clone(), draw_info, border)
+            .unwrap_or(placement_info);
+
+        let mut page_master = self.page_master.lock().unwrap();
+        page_master.set_group(f.get_group(), &last_placement);
+        page_master.update_placement(&last_placement);
+        fits
+    }
+    ///Keeps a placement under name for place_relative.
+    pub fn name_placement(&mut self, name: &str, placement_info: PlacementInfo) {
+        self.page_master
+            .lock()
+            .unwrap()
+            .set_named_placement(name, placement_info);
+    }
+    pub fn get_named_placement(&self, name: &str) -> Option<PlacementInfo> {
+        self.page_master.lock().unwrap().get_named_placement(name)
+    }
+    ///Draws the container next to the placement kept under name, size being (width, height) in pixels.
+    ///The flow under it is pushed down if it ends up lower. None if the name is unknown.
+    pub fn place_relative<T: DrawInfoReq, F: ContainerTrait + ?Sized>(
+        &mut self,
+        name: &str,
+        relative: RelativePlacement,
+        size_pixels: (f64, f64),
+        draw_info: &mut T,
+        f: &mut F,
+        border: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        let target = self.get_named_placement(name)?;
+        let rec = relative.get_rec(&target.rec, size_pixels.0, size_pixels.1);
+        let placement_info = self
+            .page_master
+            .lock()
+            .unwrap()
+            .get_absolute_position(target.page_number, rec);
+        if placement_info.page_number >= draw_info.page_array_size() {
+            draw_info.increment_page_buffer(placement_info.page_number);
+        }
+        let last_placement = f
+            .on_draw(placement_info.clone(), draw_info, border)
+            .unwrap_or(placement_info);
+
+        let mut page_master = self.page_master.lock().unwrap();
+        page_master.set_group(f.get_group(), &last_placement);
+        page_master.reserve_placement(&last_placement);
+        Some(last_placement)
+    }
+    ///Draws the container at an exact spot on a page, making the page if it isn't there yet.
+    ///The columns aren't updated so the rest of the flow is not pushed down.
+    pub fn place_absolute<T: DrawInfoReq, F: ContainerTrait + ?Sized>(
+        &mut self,
+        page_number: usize,
+        rec: Rectangle,
+        draw_info: &mut T,
+        f: &mut F,
+        border: &Option<RefCell<Vec<Border>>>,
+    ) -> PlacementInfo {
+        let placement_info = self
+            .page_master
+            .lock()
+            .unwrap()
+            .get_absolute_position(page_number, rec);
+        if page_number >= draw_info.page_array_size() {
+            draw_info.increment_page_buffer(page_number);
+        }
+        let last_placement = f
+            .on_draw(placement_info.clone(), draw_info, border)
+            .unwrap_or(placement_info);
+
+        self.page_master
+            .lock()
+            .unwrap()
+            .set_group(f.get_group(), &last_placement);
+        last_placement
+    }
+    ///Saves the column state and how much has been drawn so far. Hand it to rollback to undo
+    ///everything placed since, or to commit to keep it.
+    pub fn checkpoint<T: DrawInfoReq>(
+        &self,
+        draw_info: &T,
+        border: &Option<RefCell<Vec<Border>>>,
+    ) -> Checkpoint {
+        Checkpoint {
+            page_master: self.page_master.lock().unwrap().clone(),
+            page_operation_lens: (0..draw_info.page_array_size())
+                .map(|x| draw_info.page_operation_len(x))
+                .collect(),
+            border_cnt: border.as_ref().map_or(0, |x| x.borrow().len()),
+        }
+    }
+    ///Puts the columns, pages and borders back the way they were at the checkpoint.
+    pub fn rollback<T: DrawInfoReq>(
+        &mut self,
+        checkpoint: Checkpoint,
+        draw_info: &mut T,
+        border: &Option<RefCell<Vec<Border>>>,
+    ) {
+        *self.page_master.lock().unwrap() = checkpoint.page_master;
+        draw_info.truncate_pages(checkpoint.page_operation_lens.len());
+        for (page_num, len) in checkpoint.page_operation_lens.into_iter().enumerate() {
+            draw_info.truncate_page(page_num, len);
+        }
+        if let Some(border) = border {
+            border.borrow_mut().truncate(checkpoint.border_cnt);
+        }
+    }
+    ///Keeps everything placed since the checkpoint.
+    pub fn commit(&mut self, checkpoint: Checkpoint) {
+        drop(checkpoint);
+    }
+    ///Queues a container to be laid out and drawn by build instead of right away.
+    pub fn queue(&mut self, location: LocationInfo, container: Box<dyn Container>) {
+        self.container.push_back((location, container));
+    }
+    pub fn get_queue_len(&self) -> usize {
+        self.container.len()
+    }
+    ///Lays out the queued containers in passes until the page numbers settle, then draws them.
+    ///Before every pass each container gets the LayoutContext of the pass before it.
+    ///Every container is drawn once per pass, except the ones that say they can't be redrawn,
+    ///which only take up their content height until the last pass.
+    ///Pages drawn before build count toward total_pages, pages drawn after it can't.
+    pub fn build<T: DrawInfoReq>(
+        &mut self,
+        draw_info: &mut T,
+        border: &Option<RefCell<Vec<Border>>>,
+    ) -> LayoutContext {
+        let start_state = self.page_master.lock().unwrap().clone();
+        let mut forced_breaks = vec![false; self.container.len()];
+        let mut layout = LayoutContext::default();
+
+        for pass in 0..MAX_LAYOUT_PASSES {
+            layout.pass = pass;
+            let mut scratch = LayoutDrawInfo::default();
+            let placements =
+                self.run_layout_pass(&layout, &forced_breaks, &mut scratch, &None, false);
+            let page_cnt = (self.page_master.lock().unwrap().get_page_cnt() + 1)
+                .max(scratch.page_array_size())
+                .max(draw_info.page_array_size());
+            *self.page_master.lock().unwrap() = start_state.clone();
+
+            let mut breaks_changed = false;
+            for index in 1..placements.len() {
+                let this = &placements[index - 1];
+                let next = &placements[index];
+                if self.container[index - 1].0.keep_with_next
+                    && !forced_breaks[index - 1]
+                    && !this.is_new_page
+                    && this.page_number != next.page_number
+                {
+                    forced_breaks[index - 1] = true;
+                    breaks_changed = true;
+                }
+            }
+
+            let found = self.get_layout_context(pass + 1, &placements, page_cnt);
+            let settled = found.is_settled_with(&layout);
+            layout = found;
+            if settled && !breaks_changed {
+                break;
+            }
+        }
+
+        self.run_layout_pass(&layout, &forced_breaks, draw_info, border, true);
+        self.container.clear();
+        layout
+    }
+    fn run_layout_pass<T: DrawInfoReq>(
+        &mut self,
+        layout: &LayoutContext,
+        forced_breaks: &[bool],
+        draw_info: &mut T,
+        border: &Option<RefCell<Vec<Border>>>,
+        is_final: bool,
+    ) -> Vec<PlacementInfo> {
+        let mut placements = Vec::with_capacity(self.container.len());
+        for (index, (location, container)) in self.container.iter_mut().enumerate() {
+            container.on_layout(layout);
+            let mut placement_handle = CurrentPlacement::new_with_options(
+                &self.page_master,
+                Some(location.range),
+                PlacementOptions {
+                    move_to_next_page: location.start_new_page || forced_breaks[index],
+                    keep_together: location.keep_together,
+                    ..Default::default()
+                },
+            );
+            match location.height {
+                Some(height) => placement_handle.set_pixel_height(height),
+                None => placement_handle.set_height_from_content(container, None, None),
+            }
+            if is_final || container.can_redraw() {
+                placement_handle.draw(container, draw_info, border);
+            } else {
+                placement_handle.reserve(container);
+            }
+            placements.push(placement_handle.get_placement_info());
+        }
+        placements
+    }
+    fn get_layout_context(
+        &self,
+        pass: usize,
+        placements: &[PlacementInfo],
+        page_cnt: usize,
+    ) -> LayoutContext {
+        let page_numbers: Vec<usize> = placements.iter().map(|x| x.page_number).collect();
+        let mut named_pages = BTreeMap::new();
+        for (index, (location, _)) in self.container.iter().enumerate() {
+            if let Some(name) = &location.name {
+                named_pages.insert(name.clone(), page_numbers[index]);
+            }
+        }
+        LayoutContext {
+            pass,
+            total_pages: page_numbers.iter().max().map_or(0, |x| x + 1).max(page_cnt),
+            page_numbers,
+            named_pages,
+        }
+    }
+    ///Like place_now but the height comes from the container's content, clamped to the optional min and max.
+    pub fn place_auto_height<S: Into<Span>, T: DrawInfoReq, F: ContainerTrait + ?Sized>(
+        &mut self,
+        range: S,
+        min_height_pixels: Option<f64>,
+        max_height_pixels: Option<f64>,
+        draw_info: &mut T,
+        f: &mut F,
+        border: &Option<RefCell<Vec<Border>>>,
+    ) -> PlacementInfo {
+        let mut placement_handle = self.get_placement_handle(range, false);
+        placement_handle.set_height_from_content(f, min_height_pixels, max_height_pixels);
+        placement_handle.draw(f, draw_info, border);
+        placement_handle.get_placement_info()
+    }
 }
 
 //#[derive(Clone)]
@@ -99,6 +476,9 @@ pub struct CurrentPlacement {
     pm: Arc<Mutex<PageMaster>>,
     placement_info: PlacementInfo,
     placement_info_to_pass_for_draw: Option<PlacementInfo>,
+    keep_with_next_pixels: f64,
+    keep_together: bool,
+    name: Option<String>,
 }
 impl Default for CurrentPlacement {
     fn default() -> Self {
@@ -106,6 +486,9 @@ impl Default for CurrentPlacement {
             pm: Arc::new(Mutex::new(PageMaster::default())),
             placement_info: Default::default(),
             placement_info_to_pass_for_draw: None,
+            keep_with_next_pixels: 0.0,
+            keep_together: false,
+            name: None,
         }
     }
 }
@@ -125,36 +508,35 @@ impl Display for CurrentPlacement {
 impl CurrentPlacement {
     pub fn new(
         page_master: &Arc<Mutex<PageMaster>>,
-        range: Option<Range<usize>>,
+        range: Option<Span>,
         start_new_page: bool,
     ) -> Self {
-
-        let mut rn = range.unwrap_or( 0..100 ).clone();
-        let col_len = page_master.lock().unwrap().get_column_len();
-
-        if rn.start > col_len {
-            rn.start = 0;
-        }
-
-        if rn.end > col_len {
-            rn.end = col_len - 1;
-        }
-        let placement_info =
-            page_master
-                .clone()
-                .lock()
-                .unwrap()
-                .get_next_top_position(
-                PlacementOptions {
-                    move_to_next_page: start_new_page,
-                    ignore: false,
-                },rn
-            ,
-        );
+        Self::new_with_options(
+            page_master,
+            range,
+            PlacementOptions {
+                move_to_next_page: start_new_page,
+                ..Default::default()
+            },
+        )
+    }
+    pub fn new_with_options(
+        page_master: &Arc<Mutex<PageMaster>>,
+        range: Option<Span>,
+        options: PlacementOptions,
+    ) -> Self {
+        let placement_info = {
+            let mut pm = page_master.lock().unwrap();
+            let rn = pm.get_percent_range(range.unwrap_or_default());
+            pm.get_next_top_position(options.clone(), rn)
+        };
         let this = Self {
             pm: page_master.clone(),
             placement_info,
             placement_info_to_pass_for_draw: None,
+            keep_with_next_pixels: options.keep_with_next_pixels,
+            keep_together: options.keep_together,
+            name: None,
         };
         this
     }
@@ -164,13 +546,51 @@ impl CurrentPlacement {
         //   get_next_top_position(PlacementOptions{ move_to_next_page: false, ignore: false },
         //                       self.placement_info.percent_range.clone(), );
 
-        if self.placement_info.draw_height_left_on_page >= height {
+        //a fresh page is as good as it gets for whatever has to come along
+        let room_needed = if self.placement_info.is_new_page {
+            height
+        } else {
+            height + self.keep_with_next_pixels
+        };
+        if self.placement_info.draw_height_left_on_page >= room_needed {
             self.placement_info.rec.height = height;
         } else {
             self.placement_info = self.pm.lock().unwrap().get_next_top_position(
                 PlacementOptions {
                     move_to_next_page: true,
-                    ignore: false,
+                    ..Default::default()
+                },
+                self.placement_info.percent_range.clone(),
+            );
+            self.placement_info.rec.height = height;
+        }
+    }
+
+    ///Room that has to stay free under this placement on the same page, checked when the height is set.
+    pub fn set_keep_with_next(&mut self, pixels: f64) {
+        self.keep_with_next_pixels = pixels;
+    }
+
+    ///Checked against the container's content height when it's drawn or reserved.
+    pub fn set_keep_together(&mut self, keep_together: bool) {
+        self.keep_together = keep_together;
+    }
+    //moves to the top of the next page, keeping the height, when what the container says it
+    //needs doesn't fit under the placement.
+    fn keep_content_together<F: ContainerTrait + ?Sized>(&mut self, f: &F) {
+        if !self.keep_together || self.placement_info.is_new_page {
+            return;
+        }
+        let content_height = f
+            .get_content_height(self.placement_info.rec.width, &self.placement_info.page_size_info)
+            .unwrap_or(0.0)
+            .max(self.placement_info.rec.height);
+        if self.placement_info.draw_height_left_on_page < content_height {
+            let height = self.placement_info.rec.height;
+            self.placement_info = self.pm.lock().unwrap().get_next_top_position(
+                PlacementOptions {
+                    move_to_next_page: true,
+                    ..Default::default()
                 },
                 self.placement_info.percent_range.clone(),
             );
@@ -182,6 +602,29 @@ impl CurrentPlacement {
         self.update(size);
     }
 
+    ///Sets the height to what the container reports it needs, clamped to the optional min and max.
+    ///Containers that can't measure themselves get the min height, or nothing.
+    pub fn set_height_from_content<F: ContainerTrait + ?Sized>(
+        &mut self,
+        f: &F,
+        min_height_pixels: Option<f64>,
+        max_height_pixels: Option<f64>,
+    ) {
+        let mut height = f
+            .get_content_height(
+                self.placement_info.rec.width,
+                &self.placement_info.page_size_info,
+            )
+            .unwrap_or(0.0);
+        if let Some(min_height) = min_height_pixels {
+            height = height.max(min_height);
+        }
+        if let Some(max_height) = max_height_pixels {
+            height = height.min(max_height);
+        }
+        self.update(height);
+    }
+
     pub fn get_placement_info(&mut self) -> PlacementInfo {
         self.placement_info.clone()
     }
@@ -193,9 +636,10 @@ impl CurrentPlacement {
         border: &Option<RefCell<Vec<Border>>>,
     ) -> ()
     where
-        F: ContainerTrait,
+        F: ContainerTrait + ?Sized,
         T: DrawInfoReq,
     {
+        self.keep_content_together(f);
         let draw_rec = if let Some(place) = self.placement_info_to_pass_for_draw.clone() {
             place
         } else {
@@ -207,7 +651,14 @@ impl CurrentPlacement {
         if let Some(last_placement) = f.on_draw(draw_rec, draw_info, border) {
             self.placement_info = last_placement;
         }
-
+        self.mark_used(f);
+    }
+    ///Marks the placement as used on the page master without drawing anything into it.
+    pub fn reserve<F: ContainerTrait + ?Sized>(&mut self, f: &F) {
+        self.keep_content_together(f);
+        self.mark_used(f);
+    }
+    fn mark_used<F: ContainerTrait + ?Sized>(&mut self, f: &F) {
         self.pm
             .lock()
             .unwrap()
@@ -217,6 +668,16 @@ impl CurrentPlacement {
             .lock()
             .unwrap()
             .update_placement(&self.placement_info);
+        if let Some(name) = &self.name {
+            self.pm
+                .lock()
+                .unwrap()
+                .set_named_placement(name, self.placement_info.clone());
+        }
+    }
+    ///Keeps where this gets drawn under name, for Manager::place_relative.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
     }
     pub fn set_restricted_interior(
         &mut self,
@@ -235,3 +696,281 @@ impl CurrentPlacement {
         self.placement_info.restricted_area_option = Some(area_opt);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::placement_info::{EdgeAlignment, Relation};
+    use crate::container::test_helpers::{Block, Drawn, Pages};
+
+    #[test]
+    fn total_pages_counts_pages_drawn_before_build() {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        let mut draw_info = LayoutDrawInfo::default();
+        let drawn = Drawn::default();
+        for page in 0..3 {
+            let mut container = Block::new(100.0, &drawn);
+            let mut placement_handle = manager.get_placement_handle(0..100, page > 0);
+            placement_handle.set_pixel_height(100.0);
+            placement_handle.draw(&mut container, &mut draw_info, &None);
+        }
+        manager.queue(LocationInfo::new(0..100, None), Box::new(Block::new(100.0, &drawn)));
+        let layout = manager.build(&mut draw_info, &None);
+        assert_eq!(layout.total_pages, 3);
+    }
+
+    #[test]
+    fn mirrored_margins_carry_across_section_breaks() {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        manager.set_mirrored_margins(1.0, 0.5);
+        assert_eq!(manager.get_page_info().inside_margin, 72.0);
+        let page = manager.section_break(PageSizeInfo::from_inches(11.0, 8.5, 72.0, 0.5, 0.5));
+        assert_eq!(page, 0);
+        let mut placement_handle = manager.get_placement_handle(0..100, true);
+        placement_handle.set_pixel_height(100.0);
+        let placement = placement_handle.get_placement_info();
+        assert_eq!(placement.page_number, 1);
+        assert_eq!(placement.rec.x, 36.0);
+        assert_eq!(placement.rec.width, 792.0 - 108.0);
+        assert_eq!(manager.get_page_info().outside_margin, 36.0);
+    }
+
+    #[test]
+    fn rollback_drops_what_was_drawn_since_the_checkpoint() {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        let mut draw_info = Pages::default();
+        let drawn = Drawn::default();
+        let mut kept = Block::new(100.0, &drawn);
+        let mut placement_handle = manager.get_placement_handle(0..100, false);
+        placement_handle.set_pixel_height(100.0);
+        placement_handle.draw(&mut kept, &mut draw_info, &None);
+        let checkpoint = manager.checkpoint(&draw_info, &None);
+        for new_page in [false, true].iter() {
+            let mut trial = Block::new(100.0, &drawn);
+            let mut placement_handle = manager.get_placement_handle(0..100, *new_page);
+            placement_handle.set_pixel_height(100.0);
+            placement_handle.draw(&mut trial, &mut draw_info, &None);
+        }
+        assert_eq!(draw_info.pages.len(), 2);
+        assert_eq!(draw_info.pages[0].len(), 2);
+        manager.rollback(checkpoint, &mut draw_info, &None);
+        assert_eq!(draw_info.pages.len(), 1);
+        assert_eq!(draw_info.pages[0].len(), 1);
+        let placement = manager.get_placement_handle(0..100, false).get_placement_info();
+        assert_eq!(placement.page_number, 0);
+        assert_eq!(placement.rec.y, 136.0);
+    }
+
+    #[test]
+    fn build_draws_containers_that_cant_redraw_once() {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        let (once_drawn, again_drawn) = (Drawn::default(), Drawn::default());
+        let mut once = Block::new(100.0, &once_drawn);
+        once.redraw = false;
+        manager.queue(LocationInfo::new(0..100, None), Box::new(once));
+        manager.queue(LocationInfo::new(0..100, None), Box::new(Block::new(100.0, &again_drawn)));
+        manager.build(&mut LayoutDrawInfo::default(), &None);
+        assert_eq!(once_drawn.borrow().len(), 1);
+        assert!(again_drawn.borrow().len() > 1);
+    }
+
+    //the full width is used down to 100 pixels above the bottom margin of the first page
+    fn filled() -> (Manager, Pages) {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        let mut pages = Pages::default();
+        let mut placement_handle = manager.get_placement_handle(0..100, false);
+        placement_handle.set_pixel_height(620.0);
+        placement_handle.draw(&mut Block::new(620.0, &Drawn::default()), &mut pages, &None);
+        (manager, pages)
+    }
+
+    #[test]
+    fn keep_together_moves_a_block_that_does_not_fit() {
+        let (mut manager, _) = filled();
+        assert!(!manager.keep_together(0..100, 100.0));
+        assert!(manager.keep_together(0..100, 150.0));
+        let placement = manager.get_placement_handle(0..100, false).get_placement_info();
+        assert_eq!(placement.page_number, 1);
+        assert_eq!(placement.rec.y, 36.0);
+    }
+
+    #[test]
+    fn keep_together_measures_the_container() {
+        for keep_together in [false, true].iter() {
+            let (mut manager, mut pages) = filled();
+            let drawn = Drawn::default();
+            let options = PlacementOptions {
+                keep_together: *keep_together,
+                ..Default::default()
+            };
+            let mut placement_handle = manager.get_placement_handle_with_options(0..100, options);
+            placement_handle.set_pixel_height(50.0);
+            placement_handle.draw(&mut Block::new(300.0, &drawn), &mut pages, &None);
+            let placement = &drawn.borrow()[0];
+            assert_eq!(placement.page_number, *keep_together as usize);
+            assert_eq!(placement.rec.height, 50.0);
+        }
+    }
+
+    #[test]
+    fn keep_together_applies_to_queued_containers() {
+        let (mut manager, mut pages) = filled();
+        let drawn = Drawn::default();
+        let location = LocationInfo {
+            keep_together: true,
+            ..LocationInfo::new(0..100, Some(50.0))
+        };
+        manager.queue(location, Box::new(Block::new(300.0, &drawn)));
+        manager.build(&mut pages, &None);
+        assert_eq!(drawn.borrow().last().unwrap().page_number, 1);
+    }
+
+    #[test]
+    fn keep_with_next_leaves_room_under_the_placement() {
+        let (mut manager, _) = filled();
+        let options = PlacementOptions {
+            keep_with_next_pixels: 80.0,
+            ..Default::default()
+        };
+        let mut placement_handle = manager.get_placement_handle_with_options(0..100, options);
+        placement_handle.set_pixel_height(50.0);
+        assert_eq!(placement_handle.get_placement_info().page_number, 1);
+    }
+
+    #[test]
+    fn absolute_placements_leave_the_flow_alone() {
+        let (mut manager, mut pages) = filled();
+        let drawn = Drawn::default();
+        for page_number in [0, 2].iter() {
+            let rec = Rectangle::new(300.0, 700.0, 100.0, 40.0);
+            manager.place_absolute(*page_number, rec, &mut pages, &mut Block::new(40.0, &drawn), &None);
+        }
+        let drawn = drawn.borrow();
+        assert_eq!(drawn[0].page_number, 0);
+        assert_eq!((drawn[0].rec.x, drawn[0].rec.y), (300.0, 700.0));
+        assert_eq!(drawn[1].page_number, 2);
+        assert_eq!(pages.pages.len(), 3);
+        let next = manager.get_placement_handle(0..100, false).get_placement_info();
+        assert_eq!(next.page_number, 0);
+        assert_eq!(next.rec.y, 656.0);
+    }
+
+    #[test]
+    fn page_break_moves_every_column() {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        let mut placement_handle = manager.get_placement_handle(0..50, false);
+        placement_handle.set_pixel_height(100.0);
+        placement_handle.draw(&mut Block::new(100.0, &Drawn::default()), &mut Pages::default(), &None);
+        assert_eq!(manager.page_break(), 1);
+        let placement = manager.get_placement_handle(50..100, false).get_placement_info();
+        assert_eq!(placement.page_number, 1);
+        assert_eq!(placement.rec.y, 36.0);
+        assert_eq!(manager.page_break(), 2);
+    }
+
+    #[test]
+    fn odd_and_even_breaks_skip_a_page_when_needed() {
+        let (mut manager, _) = filled();
+        assert_eq!(manager.break_to_odd_page(), 2);
+        let (mut manager, _) = filled();
+        assert_eq!(manager.break_to_even_page(), 1);
+        assert_eq!(manager.break_to_odd_page(), 2);
+    }
+
+    #[test]
+    fn odd_and_even_breaks_stay_on_an_empty_page_that_matches() {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        assert_eq!(manager.break_to_odd_page(), 0);
+        assert_eq!(manager.break_to_even_page(), 1);
+        assert_eq!(manager.break_to_even_page(), 1);
+        assert_eq!(manager.break_to_odd_page(), 2);
+        let placement = manager.get_placement_handle(0..100, false).get_placement_info();
+        assert_eq!(placement.page_number, 2);
+    }
+
+    //(anchor, page, top) for a 40 pixel block under filled(), free from 656 to 756 on page 0
+    #[test]
+    fn anchored_blocks_go_at_their_anchor() {
+        let expected = [
+            (Anchor::Top, 0, 656.0),
+            (Anchor::Center, 0, 686.0),
+            (Anchor::Bottom, 0, 716.0),
+            (Anchor::PageTop, 1, 36.0),
+            (Anchor::PageCenter, 1, 376.0),
+            (Anchor::PageBottom, 0, 716.0),
+        ];
+        for (anchor, page_number, top) in expected.iter() {
+            let (mut manager, mut pages) = filled();
+            let drawn = Drawn::default();
+            let fits = manager.place_anchored(40.0, 0..100, *anchor, &mut pages, &mut Block::new(40.0, &drawn), &None);
+            let placement = &drawn.borrow()[0];
+            assert_eq!(fits, *page_number == 0);
+            assert_eq!((placement.page_number, placement.rec.y), (*page_number, *top));
+            let next = manager.get_placement_handle(0..100, false).get_placement_info();
+            assert_eq!((next.page_number, next.rec.y), (*page_number, top + 40.0));
+        }
+    }
+
+    #[test]
+    fn anchored_blocks_that_do_not_fit_go_on_the_next_page() {
+        let (mut manager, mut pages) = filled();
+        let drawn = Drawn::default();
+        let fits = manager.place_anchored(150.0, 0..100, Anchor::Bottom, &mut pages, &mut Block::new(150.0, &drawn), &None);
+        assert!(!fits);
+        let placement = &drawn.borrow()[0];
+        assert_eq!((placement.page_number, placement.rec.y), (1, 606.0));
+    }
+
+    //a 100 pixel block named "logo" at the top of the left half of page 0
+    fn named() -> (Manager, Pages, PlacementInfo) {
+        let mut manager = Manager::new(8.5, 11.0, 72.0, 0.5, 0.5);
+        let mut pages = Pages::default();
+        let mut placement_handle = manager.get_placement_handle(0..50, false);
+        placement_handle.set_name("logo");
+        placement_handle.set_pixel_height(100.0);
+        placement_handle.draw(&mut Block::new(100.0, &Drawn::default()), &mut pages, &None);
+        let logo = manager.get_named_placement("logo").unwrap();
+        (manager, pages, logo)
+    }
+
+    #[test]
+    fn relative_placements_go_next_to_the_named_one() {
+        let (mut manager, mut pages, logo) = named();
+        let drawn = Drawn::default();
+        let relative = RelativePlacement::new(Relation::RightOf, EdgeAlignment::Center).with_offset(10.0, 0.0);
+        let placement = manager
+            .place_relative("logo", relative, (80.0, 40.0), &mut pages, &mut Block::new(40.0, &drawn), &None)
+            .unwrap();
+        assert_eq!(placement.page_number, 0);
+        assert_eq!(placement.rec.x, logo.rec.x + logo.rec.width + 10.0);
+        assert_eq!(placement.rec.y, logo.rec.y + 30.0);
+        assert_eq!(drawn.borrow().len(), 1);
+        //the flow under it is pushed down, the named one's column is left where it was
+        let right = manager.get_placement_handle(50..100, false).get_placement_info();
+        assert_eq!(right.rec.y, logo.rec.y + 70.0);
+        let left = manager.get_placement_handle(0..50, false).get_placement_info();
+        assert_eq!(left.rec.y, logo.rec.y + 100.0);
+    }
+
+    #[test]
+    fn relative_placements_below_push_the_flow_down() {
+        let (mut manager, mut pages, logo) = named();
+        let relative = RelativePlacement::new(Relation::Below, EdgeAlignment::Start);
+        let placement = manager
+            .place_relative("logo", relative, (100.0, 40.0), &mut pages, &mut Block::new(40.0, &Drawn::default()), &None)
+            .unwrap();
+        assert_eq!((placement.rec.x, placement.rec.y), (logo.rec.x, logo.rec.y + 100.0));
+        let next = manager.get_placement_handle(0..50, false).get_placement_info();
+        assert_eq!(next.rec.y, logo.rec.y + 140.0);
+    }
+
+    #[test]
+    fn relative_placements_need_a_known_name() {
+        let (mut manager, mut pages, _) = named();
+        let drawn = Drawn::default();
+        let relative = RelativePlacement::new(Relation::Above, EdgeAlignment::End);
+        let placement = manager.place_relative("missing", relative, (10.0, 10.0), &mut pages, &mut Block::new(10.0, &drawn), &None);
+        assert!(placement.is_none());
+        assert!(drawn.borrow().is_empty());
+    }
+}
@@ -1,6 +1,10 @@
 pub mod container_trait;
+pub mod layout_context;
 pub mod manager;
 pub mod page_master;
 pub mod page_size_info;
 pub mod placement_info;
 pub mod rectangle;
+pub mod span;
+#[cfg(test)]
+pub(crate) mod test_helpers;
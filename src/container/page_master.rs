@@ -1,45 +1,96 @@
 use crate::container::page_size_info::PageSizeInfo;
-use crate::container::placement_info::{PlacementInfo, PlacementOptions};
+use crate::container::placement_info::{Anchor, PlacementInfo, PlacementOptions};
 use crate::container::rectangle::Rectangle;
+use crate::container::span::Span;
 use std::collections::BTreeMap;
 use std::ops::Range;
+use std::sync::Arc;
+
+//a stretch of the page width, in percent, and how far down which page it has been filled.
+#[derive(Clone)]
+struct Column {
+    start: f64,
+    end: f64,
+    page: usize,
+    height: f64,
+}
 
 pub struct PageMargins {
     pub top_margin_pixels: f64,
     pub bottom_margin_pixels: f64,
 }
+#[derive(Clone, Default)]
 pub struct PageMaster {
-    columns: Vec<(usize, f64)>,
-    page_width_pixels: f64,
-    page_height_pixels: f64,
-    dpi: f64,
-    top_margin_inch: f64,
-    bottom_margin_inch: f64,
+    columns: Vec<Column>,
+    //page size and margins by the first page they apply to
+    layouts: Arc<BTreeMap<usize, PageSizeInfo>>,
     pub groups: BTreeMap<usize, Vec<Vec<Rectangle>>>,
-}
-impl Default for PageMaster {
-    fn default() -> Self {
-        Self {
-            columns: vec![],
-            page_width_pixels: 0.0,
-            page_height_pixels: 0.0,
-            dpi: 0.0,
-            top_margin_inch: 0.0,
-            bottom_margin_inch: 0.0,
-            groups: Default::default(),
-        }
-    }
+    //highest page something was placed on outside of the column flow
+    absolute_page: usize,
+    //inside and outside margins set for the whole document, later sections get them too
+    mirrored_margins: Option<(f64, f64)>,
+    named_placements: BTreeMap<String, PlacementInfo>,
 }
 impl PageMaster {
     pub fn get_page_cnt(&self) -> usize {
-        let (largest_page, _) = self.get_largest_for_range(0..100);
-        largest_page
+        let (largest_page, _) = self.get_largest_for_range(0.0..100.0);
+        largest_page.max(self.absolute_page)
     }
-    pub fn get_column_len(&self) -> usize {
-        self.columns.len()
+    ///The span as a percent range of the current page's content width, columns from its column layout.
+    pub fn get_percent_range(&self, span: Span) -> Range<f64> {
+        self.get_current_page_size_info().get_percent_range(span)
     }
     pub fn get_page_info(&self) -> (f64, f64, f64) {
-        (self.page_width_pixels, self.page_height_pixels, self.dpi)
+        let info = self.get_current_page_size_info();
+        (info.page_width_pixels, info.page_height_pixels, info.dpi)
+    }
+    ///Size and margins of the page, from the last section break at or before it.
+    pub fn get_page_size_info(&self, page_number: usize) -> PageSizeInfo {
+        self.layouts
+            .range(..=page_number)
+            .next_back()
+            .map(|x| x.1.clone())
+            .unwrap_or_else(|| PageSizeInfo::new(0.0, 0.0, 0.0, 0.0, 0.0))
+    }
+    ///Size and margins of the page the flow is currently on.
+    pub fn get_current_page_size_info(&self) -> PageSizeInfo {
+        let (largest_page, _) = self.get_largest_for_range(0.0..100.0);
+        self.get_page_size_info(largest_page)
+    }
+    ///Sets the inside and outside margins, in pixels, of every page. Sections started later
+    ///get them too unless they bring mirrored margins of their own.
+    pub fn set_mirrored_margins(&mut self, inside_margin: f64, outside_margin: f64) {
+        self.mirrored_margins = Some((inside_margin, outside_margin));
+        for layout in Arc::make_mut(&mut self.layouts).values_mut() {
+            layout.inside_margin = inside_margin;
+            layout.outside_margin = outside_margin;
+        }
+    }
+    ///Starts a new page using the page size and margins from then on. Returns the new page's number.
+    pub fn section_break(&mut self, mut page_size_info: PageSizeInfo) -> usize {
+        let (_, largest_height) = self.get_largest_for_range(0.0..100.0);
+        let page_cnt = self.get_page_cnt();
+        //nothing placed yet, the first page takes the new layout
+        let page_number = if page_cnt == 0 && largest_height <= self.get_page_size_info(0).top_margin {
+            0
+        } else {
+            page_cnt + 1
+        };
+        //a later break that was already set up is replaced by this one
+        Arc::make_mut(&mut self.layouts).split_off(&page_number);
+        self.columns = vec![Column {
+            start: 0.0,
+            end: 100.0,
+            page: page_number,
+            height: page_size_info.top_margin,
+        }];
+        if let Some((inside_margin, outside_margin)) = self.mirrored_margins {
+            if page_size_info.inside_margin == 0.0 && page_size_info.outside_margin == 0.0 {
+                page_size_info = page_size_info.with_mirrored_margins(inside_margin, outside_margin);
+            }
+        }
+        Arc::make_mut(&mut self.layouts).insert(page_number, page_size_info);
+        page_number
     }
     pub fn new(
         page_width_pixels: f64,
@@ -48,20 +99,43 @@ impl PageMaster {
         top_margin_inch: f64,
         bottom_margin_inch: f64,
     ) -> Self {
+        let mut layouts = BTreeMap::new();
+        layouts.insert(
+            0,
+            PageSizeInfo::new(
+                page_width_pixels,
+                page_height_pixels,
+                dpi,
+                top_margin_inch * dpi,
+                bottom_margin_inch * dpi,
+            ),
+        );
         Self {
-            columns: vec![(0, top_margin_inch * dpi); 100],
-            page_height_pixels,
-            page_width_pixels,
-            dpi,
-            top_margin_inch,
-            bottom_margin_inch,
+            columns: vec![Column {
+                start: 0.0,
+                end: 100.0,
+                page: 0,
+                height: top_margin_inch * dpi,
+            }],
+            layouts: Arc::new(layouts),
             groups: Default::default(),
+            absolute_page: 0,
+            mirrored_margins: None,
+            named_placements: BTreeMap::new(),
         }
     }
+    ///Keeps a placement so later ones can be positioned relative to it.
+    pub fn set_named_placement(&mut self, name: &str, placement_info: PlacementInfo) {
+        self.named_placements.insert(name.to_string(), placement_info);
+    }
+    pub fn get_named_placement(&self, name: &str) -> Option<PlacementInfo> {
+        self.named_placements.get(name).cloned()
+    }
     pub fn get_margins_pixels(&self) -> PageMargins {
+        let info = self.get_current_page_size_info();
         PageMargins {
-            top_margin_pixels: self.top_margin_inch * self.dpi,
-            bottom_margin_pixels: self.bottom_margin_inch * self.dpi,
+            top_margin_pixels: info.top_margin,
+            bottom_margin_pixels: info.bottom_margin,
         }
     }
     pub fn set_group(&mut self, group_id: Option<usize>, rec: &PlacementInfo) {
@@ -145,7 +219,7 @@ impl PageMaster {
     pub fn get_next_top_position(
         &mut self,
         options: PlacementOptions,
-        range: Range<usize>,
+        range: Range<f64>,
     ) -> PlacementInfo {
         let (mut largest_page, mut largest_height_on_largest_page) =
             self.get_largest_for_range(range.clone());
@@ -153,59 +227,175 @@ impl PageMaster {
         if options.move_to_next_page {
             largest_page = largest_page + 1;
             is_new_page = true;
-            largest_height_on_largest_page = self.top_margin_inch * self.dpi;
-        } else {
-            if largest_height_on_largest_page < self.top_margin_inch * self.dpi {
-                is_new_page = true;
-                largest_height_on_largest_page = self.top_margin_inch * self.dpi;
-            }
+            largest_height_on_largest_page = self.get_page_size_info(largest_page).top_margin;
+        }
+        let page_size_info = self.get_page_size_info(largest_page);
+        if largest_height_on_largest_page < page_size_info.top_margin {
+            is_new_page = true;
+            largest_height_on_largest_page = page_size_info.top_margin;
         }
-        let draw_height_left_on_page = self.page_height_pixels
-            - self.bottom_margin_inch * self.dpi
+        let draw_height_left_on_page = page_size_info.page_height_pixels
+            - page_size_info.bottom_margin
             - (largest_height_on_largest_page);
 
         PlacementInfo {
             rec: Rectangle::new(
-                self.page_width_pixels * (range.start as f64 / 100.0),
+                page_size_info.get_x_for_percent(largest_page, range.start),
                 largest_height_on_largest_page,
-                self.page_width_pixels * ((range.end - range.start) as f64 / 100.0),
+                page_size_info.get_content_width() * ((range.end - range.start) / 100.0),
                 0.0,
             ),
             draw_height_left_on_page,
             page_number: largest_page,
-            page_size_info: PageSizeInfo::new(
-                self.page_width_pixels,
-                self.page_height_pixels,
-                self.dpi,
-                self.top_margin_inch * self.dpi,
-                self.bottom_margin_inch * self.dpi,
-            ),
+            page_size_info,
             percent_range: range,
             restricted_area_option: None,
+            page_layouts: self.layouts.clone(),
             is_new_page,
         }
     }
 
-    pub fn get_largest_for_range(&self, range: Range<usize>) -> (usize, f64) {
-        let largest_page = self.columns[range.clone()]
-            .iter()
-            .max_by_key(|x| x.0)
-            .unwrap()
-            .0;
+    ///Placement for a block of height at the anchor, on the next page if it doesn't fit under
+    ///what is already in the range. The bool is true when it fits on the current page.
+    pub fn get_anchored_position(
+        &mut self,
+        range: Range<f64>,
+        height: f64,
+        anchor: Anchor,
+    ) -> (PlacementInfo, bool) {
+        let mut placement_info = self.get_next_top_position(PlacementOptions::default(), range.clone());
+        let page_bottom = |x: &PlacementInfo| x.page_size_info.page_height_pixels - x.page_size_info.bottom_margin;
+        let mut top = anchor.get_top(height, placement_info.rec.y, &placement_info.page_size_info);
+        let fits = top >= placement_info.rec.y - 0.000001
+            && top + height <= page_bottom(&placement_info) + 0.000001;
+        if !fits {
+            placement_info = self.get_next_top_position(
+                PlacementOptions {
+                    move_to_next_page: true,
+                    ..Default::default()
+                },
+                range,
+            );
+            top = anchor
+                .get_top(height, placement_info.rec.y, &placement_info.page_size_info)
+                .max(placement_info.rec.y);
+        }
+        placement_info.rec.y = top;
+        placement_info.rec.height = height;
+        placement_info.draw_height_left_on_page = page_bottom(&placement_info) - top;
+        (placement_info, fits)
+    }
+
+    ///Placement for an exact spot on a page. The columns are left alone so the flow doesn't notice it.
+    pub fn get_absolute_position(&mut self, page_number: usize, rec: Rectangle) -> PlacementInfo {
+        self.absolute_page = self.absolute_page.max(page_number);
+        let page_size_info = self.get_page_size_info(page_number);
+        let percent_range = Span::pixels(rec.x - page_size_info.get_left_margin(page_number), rec.width)
+            .get_percent_range(page_size_info.get_content_width());
+
+        PlacementInfo {
+            rec,
+            is_new_page: false,
+            draw_height_left_on_page: page_size_info.page_height_pixels
+                - page_size_info.bottom_margin
+                - rec.y,
+            page_number,
+            page_size_info,
+            percent_range,
+            restricted_area_option: None,
+            page_layouts: self.layouts.clone(),
+        }
+    }
 
-        let largest_height_on_largest_page = self.columns[range.clone()]
-            .iter()
-            .filter(|x| x.0 == largest_page)
-            .map(|x| x.1)
-            .fold(0.0, |x, y| f64::max(x, y));
+    pub fn get_largest_for_range(&self, range: Range<f64>) -> (usize, f64) {
+        let overlapping = || {
+            self.columns.iter().filter(|x| {
+                (x.start < range.end && x.end > range.start)
+                    || (range.start >= range.end && x.start <= range.start && x.end > range.start)
+            })
+        };
+        let largest_page = overlapping().map(|x| x.page).max().unwrap_or(0);
+
+        let largest_height_on_largest_page = overlapping()
+            .filter(|x| x.page == largest_page)
+            .map(|x| x.height)
+            .fold(0.0, f64::max);
 
         (largest_page, largest_height_on_largest_page)
     }
 
+    ///Puts every column in the range at the top of the page after the lowest one in the range.
+    pub fn move_to_next_page(&mut self, range: Range<f64>) {
+        let (largest_page, _) = self.get_largest_for_range(range.clone());
+        self.set_columns(range, largest_page + 1, 0.0);
+    }
+
+    ///Puts every column at the top of the given page.
+    pub fn move_all_to_page(&mut self, page_number: usize) {
+        self.set_columns(0.0..100.0, page_number, 0.0);
+    }
+
+    ///Like update_placement, but only pushes the range down, never back up.
+    pub fn reserve_placement(&mut self, placement_info: &PlacementInfo) {
+        let (largest_page, largest_height) =
+            self.get_largest_for_range(placement_info.percent_range.clone());
+        let bottom = placement_info.rec.y + placement_info.rec.height;
+        if placement_info.page_number > largest_page
+            || (placement_info.page_number == largest_page && bottom > largest_height)
+        {
+            self.update_placement(placement_info);
+        }
+    }
+
     pub fn update_placement(&mut self, placement_info: &PlacementInfo) {
-        for (page, height) in self.columns[placement_info.percent_range.clone()].iter_mut() {
-            *page = placement_info.page_number;
-            *height = placement_info.rec.y + placement_info.rec.height;
+        self.set_columns(
+            placement_info.percent_range.clone(),
+            placement_info.page_number,
+            placement_info.rec.y + placement_info.rec.height,
+        );
+    }
+
+    //splits the columns at the ends of the range and fills what is inside with page and height.
+    fn set_columns(&mut self, range: Range<f64>, page: usize, height: f64) {
+        if range.start >= range.end {
+            return;
+        }
+        let mut columns = Vec::with_capacity(self.columns.len() + 2);
+        for column in self.columns.drain(..) {
+            if column.end <= range.start || column.start >= range.end {
+                columns.push(column);
+                continue;
+            }
+            if column.start < range.start {
+                columns.push(Column {
+                    end: range.start,
+                    ..column.clone()
+                });
+            }
+            if column.end > range.end {
+                columns.push(Column {
+                    start: range.end,
+                    ..column
+                });
+            }
+        }
+        columns.push(Column {
+            start: range.start,
+            end: range.end,
+            page,
+            height,
+        });
+        columns.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+
+        //neighbors that ended up the same are joined so the list stays short
+        self.columns = Vec::with_capacity(columns.len());
+        for column in columns {
+            match self.columns.last_mut() {
+                Some(last) if last.page == column.page && last.height == column.height => {
+                    last.end = column.end;
+                }
+                _ => self.columns.push(column),
+            }
         }
     }
 }
@@ -1,5 +1,7 @@
+use crate::container::span::Span;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::ops::Range;
 
 #[derive(Clone)]
 pub struct PageSizeInfo {
@@ -8,6 +10,12 @@ pub struct PageSizeInfo {
     pub dpi: f64,
     pub top_margin: f64,
     pub bottom_margin: f64,
+    ///Margin on the bound side, left on odd printed pages and right on even ones.
+    pub inside_margin: f64,
+    pub outside_margin: f64,
+    ///Columns the content width is split into for Span::Columns, with column_gutter pixels between them.
+    pub column_count: usize,
+    pub column_gutter: f64,
 }
 impl PageSizeInfo {
     pub fn new(
@@ -23,19 +31,126 @@ impl PageSizeInfo {
             dpi,
             top_margin,
             bottom_margin,
+            inside_margin: 0.0,
+            outside_margin: 0.0,
+            column_count: 1,
+            column_gutter: 0.0,
         }
     }
+    pub fn from_inches(
+        width_inches: f64,
+        height_inches: f64,
+        dpi: f64,
+        top_margin_inch: f64,
+        bottom_margin_inch: f64,
+    ) -> Self {
+        Self::new(
+            width_inches * dpi,
+            height_inches * dpi,
+            dpi,
+            top_margin_inch * dpi,
+            bottom_margin_inch * dpi,
+        )
+    }
+    ///Sets the inside and outside margins in pixels, they swap sides between odd and even pages.
+    pub fn with_mirrored_margins(mut self, inside_margin: f64, outside_margin: f64) -> Self {
+        self.inside_margin = inside_margin;
+        self.outside_margin = outside_margin;
+        self
+    }
+    ///Splits the content width into columns that placements can be given with Span::Columns.
+    pub fn with_columns(mut self, column_count: usize, column_gutter: f64) -> Self {
+        self.column_count = column_count.max(1);
+        self.column_gutter = column_gutter;
+        self
+    }
+    ///The span as a percent range of the content width, columns past the last one are clamped.
+    pub fn get_percent_range(&self, span: Span) -> Range<f64> {
+        let width = self.get_content_width();
+        match span {
+            Span::Columns(start, end) => {
+                let column_count = self.column_count.max(1);
+                let gutters = self.column_gutter * (column_count - 1) as f64;
+                let column_width = (width - gutters) / column_count as f64;
+                let start = start.min(column_count - 1);
+                let end = end.min(column_count).max(start + 1);
+                let left = start as f64 * (column_width + self.column_gutter);
+                let right = end as f64 * (column_width + self.column_gutter) - self.column_gutter;
+                Span::Pixels(left, right).get_percent_range(width)
+            }
+            _ => span.get_percent_range(width),
+        }
+    }
+    ///Left margin of the page, page numbers start at 0 which prints as page 1.
+    pub fn get_left_margin(&self, page_number: usize) -> f64 {
+        match page_number % 2 {
+            0 => self.inside_margin,
+            _ => self.outside_margin,
+        }
+    }
+    ///Width between the inside and outside margins, what percent ranges are taken of.
+    pub fn get_content_width(&self) -> f64 {
+        self.page_width_pixels - self.inside_margin - self.outside_margin
+    }
+    ///Pixel x of a percent of the content width on the page.
+    pub fn get_x_for_percent(&self, page_number: usize, percent: f64) -> f64 {
+        self.get_left_margin(page_number) + self.get_content_width() * (percent / 100.0)
+    }
+    ///Turns the page so the long side is the width.
+    pub fn landscape(mut self) -> Self {
+        if self.page_height_pixels > self.page_width_pixels {
+            std::mem::swap(&mut self.page_width_pixels, &mut self.page_height_pixels);
+        }
+        self
+    }
+    ///Turns the page so the long side is the height.
+    pub fn portrait(mut self) -> Self {
+        if self.page_width_pixels > self.page_height_pixels {
+            std::mem::swap(&mut self.page_width_pixels, &mut self.page_height_pixels);
+        }
+        self
+    }
 }
 impl Display for PageSizeInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Pixel Width: {}, Pixel Height: {}, DPI: {}, Top Margin: {}, Bottom Margin: {}",
+            "Pixel Width: {}, Pixel Height: {}, DPI: {}, Top Margin: {}, Bottom Margin: {}, Inside Margin: {}, Outside Margin: {}, Columns: {}",
             self.page_width_pixels,
             self.page_height_pixels,
             self.dpi,
             self.top_margin,
-            self.bottom_margin
+            self.bottom_margin,
+            self.inside_margin,
+            self.outside_margin,
+            self.column_count
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_split_the_content_width_around_gutters() {
+        let page = PageSizeInfo::new(612.0, 792.0, 72.0, 36.0, 36.0)
+            .with_mirrored_margins(36.0, 36.0)
+            .with_columns(3, 30.0);
+        let range = page.get_percent_range(Span::column(1));
+        assert!((range.start - 190.0 / 540.0 * 100.0).abs() < 1e-9);
+        assert!((range.end - 350.0 / 540.0 * 100.0).abs() < 1e-9);
+        let range = page.get_percent_range(Span::Columns(1, 5));
+        assert!((range.start - 190.0 / 540.0 * 100.0).abs() < 1e-9);
+        assert!((range.end - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn section_break_changes_the_columns() {
+        use crate::container::page_master::PageMaster;
+        let mut page_master = PageMaster::new(612.0, 792.0, 72.0, 0.5, 0.5);
+        assert_eq!(page_master.get_percent_range(Span::column(1)), 0.0..100.0);
+        page_master.section_break(PageSizeInfo::new(792.0, 612.0, 72.0, 36.0, 36.0).with_columns(2, 0.0));
+        assert_eq!(page_master.get_percent_range(Span::column(1)), 50.0..100.0);
+    }
+}
@@ -1,8 +1,10 @@
 use crate::container::page_size_info::PageSizeInfo;
 use crate::container::rectangle::Rectangle;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::ops::Range;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct PlacementInfo {
@@ -11,8 +13,10 @@ pub struct PlacementInfo {
     pub draw_height_left_on_page: f64,
     pub page_number: usize,
     pub page_size_info: PageSizeInfo,
-    pub percent_range: Range<usize>,
+    pub percent_range: Range<f64>,
     pub restricted_area_option: Option<Rectangle>,
+    ///Page size and margins by the first page they apply to, for containers that run onto later pages.
+    pub page_layouts: Arc<BTreeMap<usize, PageSizeInfo>>,
 }
 impl Default for PlacementInfo {
     fn default() -> Self {
@@ -22,8 +26,9 @@ impl Default for PlacementInfo {
             draw_height_left_on_page: 0.0,
             page_number: 0,
             page_size_info: PageSizeInfo::new(0.0, 0.0, 0.0, 0.0, 0.0),
-            percent_range: 0..1,
+            percent_range: 0.0..1.0,
             restricted_area_option: None,
+            page_layouts: Default::default(),
         }
     }
 }
@@ -34,6 +39,36 @@ impl PlacementInfo {
     pub fn get_inner_rec(&self) -> Option<Rectangle> {
         self.restricted_area_option.clone()
     }
+    ///Size and margins of a page, this placement's own when the layouts aren't known.
+    pub fn get_page_size_info(&self, page_number: usize) -> PageSizeInfo {
+        self.page_layouts
+            .range(..=page_number)
+            .next_back()
+            .map(|x| x.1.clone())
+            .unwrap_or_else(|| self.page_size_info.clone())
+    }
+    ///The same percent range at the top of a later page, for the part of a container that runs onto it.
+    pub fn get_page_placement(&self, page_number: usize) -> PlacementInfo {
+        let page_size_info = self.get_page_size_info(page_number);
+        let range_width = (self.percent_range.end - self.percent_range.start) / 100.0;
+        PlacementInfo {
+            rec: Rectangle::new(
+                page_size_info.get_x_for_percent(page_number, self.percent_range.start),
+                page_size_info.top_margin,
+                page_size_info.get_content_width() * range_width,
+                0.0,
+            ),
+            is_new_page: true,
+            draw_height_left_on_page: page_size_info.page_height_pixels
+                - page_size_info.bottom_margin
+                - page_size_info.top_margin,
+            page_number,
+            page_size_info,
+            percent_range: self.percent_range.clone(),
+            restricted_area_option: None,
+            page_layouts: self.page_layouts.clone(),
+        }
+    }
 }
 impl Display for PlacementInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -50,8 +85,102 @@ Bottom Right: {}, {}
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct PlacementOptions {
     pub move_to_next_page: bool,
-    pub ignore: bool,
+    ///Moves the placement to the next page before it's drawn when the container's own content
+    ///height doesn't fit under it, whatever height was set.
+    pub keep_together: bool,
+    ///Room in pixels that has to be left under the placement on the same page,
+    ///so a title moves to the next page instead of ending up alone at the bottom.
+    pub keep_with_next_pixels: f64,
+}
+
+///Where a block goes vertically. The first three are within the space left under what is
+///already in the range, the Page ones within the page's margins.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Anchor {
+    #[default]
+    Top,
+    Center,
+    Bottom,
+    PageTop,
+    PageCenter,
+    PageBottom,
+}
+impl Anchor {
+    ///Top of a block of height placed by this anchor, given where the free space starts.
+    pub fn get_top(&self, height: f64, free_top: f64, page_size_info: &PageSizeInfo) -> f64 {
+        let page_top = page_size_info.top_margin;
+        let page_bottom = page_size_info.page_height_pixels - page_size_info.bottom_margin;
+        match self {
+            Anchor::Top => free_top,
+            Anchor::Center => free_top + (page_bottom - free_top - height) / 2.0,
+            Anchor::Bottom => page_bottom - height,
+            Anchor::PageTop => page_top,
+            Anchor::PageCenter => page_top + (page_bottom - page_top - height) / 2.0,
+            Anchor::PageBottom => page_bottom - height,
+        }
+    }
+}
+
+///Which side of a named placement a new one goes on.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Relation {
+    Below,
+    Above,
+    RightOf,
+    LeftOf,
+}
+///How the new placement lines up along the named one's side. Start is the left edge for
+///Below and Above and the top edge for RightOf and LeftOf, End the opposite edge.
+#[derive(Clone, Copy, PartialEq)]
+pub enum EdgeAlignment {
+    Start,
+    Center,
+    End,
+}
+///Position of a new placement relative to a named one, the offsets are pixels added afterwards.
+#[derive(Clone, Copy)]
+pub struct RelativePlacement {
+    pub relation: Relation,
+    pub alignment: EdgeAlignment,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+impl RelativePlacement {
+    pub fn new(relation: Relation, alignment: EdgeAlignment) -> Self {
+        Self {
+            relation,
+            alignment,
+            offset_x: 0.0,
+            offset_y: 0.0,
+        }
+    }
+    pub fn with_offset(mut self, offset_x: f64, offset_y: f64) -> Self {
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self
+    }
+    ///Rectangle of width and height placed against target.
+    pub fn get_rec(&self, target: &Rectangle, width: f64, height: f64) -> Rectangle {
+        let align = |start: f64, target_length: f64, length: f64| match self.alignment {
+            EdgeAlignment::Start => start,
+            EdgeAlignment::Center => start + (target_length - length) / 2.0,
+            EdgeAlignment::End => start + target_length - length,
+        };
+        let (x, y) = match self.relation {
+            Relation::Below => (
+                align(target.x, target.width, width),
+                target.y + target.height,
+            ),
+            Relation::Above => (align(target.x, target.width, width), target.y - height),
+            Relation::RightOf => (
+                target.x + target.width,
+                align(target.y, target.height, height),
+            ),
+            Relation::LeftOf => (target.x - width, align(target.y, target.height, height)),
+        };
+        Rectangle::new(x + self.offset_x, y + self.offset_y, width, height)
+    }
 }
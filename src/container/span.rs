@@ -0,0 +1,75 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+///Horizontal extent of a placement. Percent spans are fractions of the content width between
+///the margins so 33.33..66.67 is a valid third, pixel spans are exact offsets from the left margin.
+///Column spans cover columns start..end of the page's column layout.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Span {
+    Percent(f64, f64),
+    Pixels(f64, f64),
+    Columns(usize, usize),
+}
+
+impl Span {
+    pub fn percent(start: f64, end: f64) -> Self {
+        Span::Percent(start, end)
+    }
+    ///An exact start, measured from the left margin, and width in pixels.
+    pub fn pixels(start: f64, width: f64) -> Self {
+        Span::Pixels(start, start + width)
+    }
+    ///A single column of the page's column layout.
+    pub fn column(index: usize) -> Self {
+        Span::Columns(index, index + 1)
+    }
+    ///The span as percents of the given width, kept inside 0..100.
+    ///Columns need the page's layout, see PageSizeInfo::get_percent_range, here they are the whole width.
+    pub fn get_percent_range(&self, width_pixels: f64) -> Range<f64> {
+        let (start, end) = match *self {
+            Span::Percent(start, end) => (start, end),
+            Span::Pixels(start, end) => (
+                start / width_pixels * 100.0,
+                end / width_pixels * 100.0,
+            ),
+            Span::Columns(_, _) => (0.0, 100.0),
+        };
+        let start = start.clamp(0.0, 100.0);
+        start..end.max(start).min(100.0)
+    }
+}
+
+impl Default for Span {
+    fn default() -> Self {
+        Span::Percent(0.0, 100.0)
+    }
+}
+
+impl From<Range<usize>> for Span {
+    fn from(range: Range<usize>) -> Self {
+        Span::Percent(range.start as f64, range.end as f64)
+    }
+}
+
+impl From<Range<i32>> for Span {
+    fn from(range: Range<i32>) -> Self {
+        Span::Percent(range.start as f64, range.end as f64)
+    }
+}
+
+impl From<Range<f64>> for Span {
+    fn from(range: Range<f64>) -> Self {
+        Span::Percent(range.start, range.end)
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Span::Percent(start, end) => write!(f, "{}%..{}%", start, end),
+            Span::Pixels(start, end) => write!(f, "{}px..{}px", start, end),
+            Span::Columns(start, end) => write!(f, "columns {}..{}", start, end),
+        }
+    }
+}
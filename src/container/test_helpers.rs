@@ -0,0 +1,120 @@
+//! Draw targets and containers shared by the unit tests.
+use crate::container::container_trait::{ContainerTrait, DrawInfoReq};
+use crate::container::page_size_info::PageSizeInfo;
+use crate::container::placement_info::PlacementInfo;
+use crate::container::rectangle::{Border, Rectangle};
+use lopdf::content::Operation;
+use lopdf::Object;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+///Keeps every operation by page, pages are added as they're written to.
+#[derive(Default)]
+pub struct Pages {
+    pub pages: Vec<Vec<Operation>>,
+}
+impl DrawInfoReq for Pages {
+    fn increment_page_buffer(&mut self, page_number: usize) {
+        if page_number >= self.pages.len() {
+            self.pages.resize(page_number + 1, Vec::new());
+        }
+    }
+    fn page_array_size(&self) -> usize {
+        self.pages.len()
+    }
+    fn insert_into_page(&mut self, page_num: usize, operation: Operation) {
+        self.increment_page_buffer(page_num);
+        self.pages[page_num].push(operation);
+    }
+    fn page_operation_len(&self, page_num: usize) -> usize {
+        self.pages.get(page_num).map_or(0, |x| x.len())
+    }
+    fn truncate_page(&mut self, page_num: usize, len: usize) {
+        if let Some(page) = self.pages.get_mut(page_num) {
+            page.truncate(len);
+        }
+    }
+    fn truncate_pages(&mut self, page_cnt: usize) {
+        self.pages.truncate(page_cnt);
+    }
+}
+impl Pages {
+    ///The text shown on each page in the order it was drawn.
+    pub fn texts(&self) -> Vec<Vec<String>> {
+        self.pages
+            .iter()
+            .map(|page| {
+                page.iter()
+                    .filter(|x| x.operator == "Tj")
+                    .filter_map(|x| match x.operands.first() {
+                        Some(Object::String(text, _)) => Some(String::from_utf8_lossy(text).to_string()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+    pub fn all_texts(&self) -> Vec<String> {
+        self.texts().into_iter().flatten().collect()
+    }
+}
+
+///Where each Block sharing it was drawn, in drawing order.
+pub type Drawn = Rc<RefCell<Vec<PlacementInfo>>>;
+
+///A container of a fixed height that draws one rectangle and remembers where it was drawn.
+pub struct Block {
+    pub height: f64,
+    pub redraw: bool,
+    pub drawn: Drawn,
+}
+impl Block {
+    pub fn new(height: f64, drawn: &Drawn) -> Self {
+        Self {
+            height,
+            redraw: true,
+            drawn: drawn.clone(),
+        }
+    }
+}
+impl ContainerTrait for Block {
+    fn on_draw<T: DrawInfoReq>(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_info: &mut T,
+        _borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        draw_info.insert_into_page(placement_info.page_number, Operation::new("re", vec![]));
+        self.drawn.borrow_mut().push(placement_info);
+        None
+    }
+    fn get_group(&self) -> Option<usize> {
+        None
+    }
+    fn get_content_height(&self, _width_pixels: f64, _page_size_info: &PageSizeInfo) -> Option<f64> {
+        Some(self.height)
+    }
+    fn can_redraw(&self) -> bool {
+        self.redraw
+    }
+}
+
+///120 pixels from the bottom of a page with 72 pixel margins. The next page is a section
+///with bigger and mirrored margins.
+pub fn placement() -> PlacementInfo {
+    let first = PageSizeInfo::new(612.0, 792.0, 72.0, 72.0, 72.0);
+    let second = PageSizeInfo::new(612.0, 792.0, 72.0, 144.0, 72.0).with_mirrored_margins(50.0, 100.0);
+    let mut layouts = BTreeMap::new();
+    layouts.insert(0, first.clone());
+    layouts.insert(1, second);
+    PlacementInfo {
+        rec: Rectangle::new(0.0, 600.0, 612.0, 0.0),
+        draw_height_left_on_page: 120.0,
+        page_size_info: first,
+        percent_range: 0.0..100.0,
+        page_layouts: Arc::new(layouts),
+        ..Default::default()
+    }
+}
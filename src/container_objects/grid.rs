@@ -0,0 +1,269 @@
+use crate::container::container_trait::{Container, ContainerTrait, DrawInfoReq};
+use crate::container::layout_context::LayoutContext;
+use crate::container::page_size_info::PageSizeInfo;
+use crate::container::placement_info::PlacementInfo;
+use crate::container::rectangle::{Border, Rectangle};
+use std::cell::RefCell;
+
+struct GridRow {
+    cells: Vec<Box<dyn Container>>,
+    height: Option<f64>,
+}
+
+///Places children side by side in columns and rows. Each row is as tall as its tallest cell,
+///every cell in a row shares the top edge and a row that doesn't fit moves to the next page whole.
+pub struct Grid {
+    column_widths_percentage: Vec<f64>,
+    rows: Vec<GridRow>,
+    gutter: f64,
+    row_spacing: f64,
+    group: Option<usize>,
+}
+
+impl Grid {
+    ///Column widths are percents of the grid width.
+    pub fn new(column_widths_percentage: Vec<f64>, group: Option<usize>) -> Self {
+        Self {
+            column_widths_percentage,
+            rows: Vec::new(),
+            gutter: 0.0,
+            row_spacing: 0.0,
+            group,
+        }
+    }
+    ///Adds a row, cells past the last column are ignored. A fixed height still grows to fit the tallest cell.
+    pub fn add_row(&mut self, cells: Vec<Box<dyn Container>>, height: Option<f64>) {
+        self.rows.push(GridRow { cells, height });
+    }
+    ///Pixels left empty between neighboring columns.
+    pub fn set_gutter(&mut self, gutter: f64) {
+        self.gutter = gutter;
+    }
+    ///Pixels left empty between rows.
+    pub fn set_row_spacing(&mut self, row_spacing: f64) {
+        self.row_spacing = row_spacing;
+    }
+
+    //x offset and width of each column inside a grid of this width.
+    fn get_cell_spans(&self, width_pixels: f64) -> Vec<(f64, f64)> {
+        let last_index = self.column_widths_percentage.len().saturating_sub(1);
+        let mut column_start = 0.0;
+        let mut spans = Vec::with_capacity(self.column_widths_percentage.len());
+        for (index, percent) in self.column_widths_percentage.iter().enumerate() {
+            let column_width = width_pixels * (percent / 100.0);
+            let left_gutter = if index > 0 { self.gutter / 2.0 } else { 0.0 };
+            let right_gutter = if index < last_index { self.gutter / 2.0 } else { 0.0 };
+            spans.push((
+                column_start + left_gutter,
+                column_width - left_gutter - right_gutter,
+            ));
+            column_start += column_width;
+        }
+        spans
+    }
+
+    fn get_row_height(row: &GridRow, spans: &[(f64, f64)], page_size_info: &PageSizeInfo) -> f64 {
+        row.cells
+            .iter()
+            .zip(spans.iter())
+            .filter_map(|(cell, span)| cell.content_height(span.1, page_size_info))
+            .fold(row.height.unwrap_or(0.0), f64::max)
+    }
+}
+
+impl ContainerTrait for Grid {
+    fn on_draw<T: DrawInfoReq>(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_to: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        if self.rows.is_empty() {
+            return None;
+        }
+        //the part of the grid on the page being filled, starting at the top of the first row on it
+        let mut fragment = placement_info.clone();
+        fragment.rec.height = 0.0;
+        let mut spans = self.get_cell_spans(fragment.rec.width);
+
+        for row_index in 0..self.rows.len() {
+            let mut row_height =
+                Self::get_row_height(&self.rows[row_index], &spans, &fragment.page_size_info);
+            let mut top = fragment.rec.y + fragment.rec.height;
+            if row_index > 0 {
+                top += self.row_spacing;
+            }
+            let mut page_bottom =
+                fragment.page_size_info.page_height_pixels - fragment.page_size_info.bottom_margin;
+            if top + row_height > page_bottom && !(fragment.is_new_page && top == fragment.rec.y) {
+                fragment = fragment.get_page_placement(fragment.page_number + 1);
+                spans = self.get_cell_spans(fragment.rec.width);
+                row_height =
+                    Self::get_row_height(&self.rows[row_index], &spans, &fragment.page_size_info);
+                top = fragment.rec.y;
+                page_bottom =
+                    fragment.page_size_info.page_height_pixels - fragment.page_size_info.bottom_margin;
+            }
+            if fragment.page_number >= draw_to.page_array_size() {
+                draw_to.increment_page_buffer(fragment.page_number);
+            }
+
+            let row_placement = PlacementInfo {
+                rec: Rectangle::new(fragment.rec.x, top, fragment.rec.width, row_height),
+                is_new_page: fragment.is_new_page && top == fragment.rec.y,
+                draw_height_left_on_page: page_bottom - top,
+                page_number: fragment.page_number,
+                page_size_info: fragment.page_size_info.clone(),
+                percent_range: fragment.percent_range.clone(),
+                restricted_area_option: None,
+                page_layouts: fragment.page_layouts.clone(),
+            };
+            for (cell, span) in self.rows[row_index].cells.iter_mut().zip(spans.iter()) {
+                let mut cell_placement = row_placement.clone();
+                cell_placement.rec = Rectangle::new(fragment.rec.x + span.0, top, span.1, row_height);
+                cell.draw(cell_placement, draw_to, borders);
+            }
+
+            fragment.rec.height = top + row_height - fragment.rec.y;
+        }
+        fragment.draw_height_left_on_page = fragment.page_size_info.page_height_pixels
+            - fragment.page_size_info.bottom_margin
+            - fragment.rec.y;
+
+        Some(fragment)
+    }
+
+    fn get_group(&self) -> Option<usize> {
+        self.group
+    }
+
+    //a grid taller than a page starts wherever its first row fits and breaks between rows,
+    //so only the first row has to fit where it's placed.
+    fn get_content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64> {
+        let spans = self.get_cell_spans(width_pixels);
+        let row_heights: Vec<f64> = self
+            .rows
+            .iter()
+            .map(|row| Self::get_row_height(row, &spans, page_size_info))
+            .collect();
+        let spacing = self.row_spacing * self.rows.len().saturating_sub(1) as f64;
+        let total = row_heights.iter().sum::<f64>() + spacing;
+        let page_height = page_size_info.page_height_pixels
+            - page_size_info.top_margin
+            - page_size_info.bottom_margin;
+        if total > page_height {
+            row_heights.first().copied()
+        } else {
+            Some(total)
+        }
+    }
+
+    fn on_layout(&mut self, layout: &LayoutContext) {
+        for row in self.rows.iter_mut() {
+            for cell in row.cells.iter_mut() {
+                cell.layout(layout);
+            }
+        }
+    }
+}
+
+///A grid with a single row, for putting a few containers next to each other.
+pub struct HStack {
+    grid: Grid,
+}
+
+impl HStack {
+    pub fn new(column_widths_percentage: Vec<f64>, group: Option<usize>) -> Self {
+        let mut grid = Grid::new(column_widths_percentage, group);
+        grid.add_row(Vec::new(), None);
+        Self { grid }
+    }
+    ///Adds the next container from the left.
+    pub fn add(&mut self, container: Box<dyn Container>) {
+        self.grid.rows[0].cells.push(container);
+    }
+    pub fn set_gutter(&mut self, gutter: f64) {
+        self.grid.set_gutter(gutter);
+    }
+    pub fn set_min_height(&mut self, height: f64) {
+        self.grid.rows[0].height = Some(height);
+    }
+}
+
+impl ContainerTrait for HStack {
+    fn on_draw<T: DrawInfoReq>(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_to: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        self.grid.on_draw(placement_info, draw_to, borders)
+    }
+
+    fn get_group(&self) -> Option<usize> {
+        self.grid.get_group()
+    }
+
+    fn get_content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64> {
+        self.grid.get_content_height(width_pixels, page_size_info)
+    }
+
+    fn on_layout(&mut self, layout: &LayoutContext) {
+        self.grid.on_layout(layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::test_helpers::{placement, Block, Drawn, Pages};
+    use std::sync::Arc;
+
+    fn grid(rows: usize, height: f64) -> (Grid, Drawn) {
+        let drawn = Drawn::default();
+        let mut grid = Grid::new(vec![100.0], None);
+        for _ in 0..rows {
+            grid.add_row(vec![Box::new(Block::new(height, &drawn))], None);
+        }
+        (grid, drawn)
+    }
+
+    #[test]
+    fn rows_past_the_page_use_the_next_page_layout() {
+        let (mut grid, drawn) = grid(4, 50.0);
+        let last = grid.on_draw(placement(), &mut Pages::default(), &None).unwrap();
+        let drawn = drawn.borrow();
+        assert_eq!(drawn[1].page_number, 0);
+        assert_eq!(drawn[2].page_number, 1);
+        assert_eq!(drawn[2].rec.y, 144.0);
+        assert_eq!(drawn[2].rec.x, 100.0);
+        assert_eq!(drawn[2].rec.width, 462.0);
+        assert_eq!(last.page_number, 1);
+        assert_eq!(last.rec.y, 144.0);
+        assert_eq!(last.rec.height, 100.0);
+    }
+
+    #[test]
+    fn rows_on_the_next_page_fit_against_its_bottom_margin() {
+        let mut placement = placement();
+        let second = PageSizeInfo::new(612.0, 792.0, 72.0, 144.0, 200.0);
+        Arc::make_mut(&mut placement.page_layouts).insert(1, second);
+        let (mut grid, drawn) = grid(7, 100.0);
+        grid.on_draw(placement, &mut Pages::default(), &None);
+        let drawn = drawn.borrow();
+        assert_eq!(drawn[1].page_number, 1);
+        assert_eq!(drawn[1].draw_height_left_on_page, 792.0 - 200.0 - 144.0);
+        assert_eq!(drawn[4].page_number, 1);
+        assert_eq!(drawn[5].page_number, 2);
+        assert_eq!(drawn[5].rec.y, 144.0);
+    }
+
+    #[test]
+    fn content_height_past_a_page_is_the_first_row() {
+        let page = PageSizeInfo::new(612.0, 792.0, 72.0, 72.0, 72.0);
+        let (short, _) = grid(4, 50.0);
+        let (tall, _) = grid(40, 50.0);
+        assert_eq!(short.get_content_height(612.0, &page), Some(200.0));
+        assert_eq!(tall.get_content_height(612.0, &page), Some(50.0));
+    }
+}
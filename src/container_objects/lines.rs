@@ -43,6 +43,32 @@ pub fn draw_rectangle(
     draw_to.insert_into_page(placement_info.page_number, Operation::new("Q", vec![]));
 }
 
+pub fn draw_filled_rectangle(
+    draw_to: &mut dyn DrawInfoReq,
+    placement_info: &PlacementInfo,
+    fill_color: (f64, f64, f64),
+) {
+    let rec = placement_info
+        .rec
+        .get_pdf_version(placement_info.page_size_info.clone());
+
+    draw_to.insert_into_page(placement_info.page_number, Operation::new("q", vec![]));
+
+    draw_to.insert_into_page(placement_info.page_number, Operation::new("CS", vec!["DeviceRGB".into()]));
+    draw_to.insert_into_page(placement_info.page_number, Operation::new(
+        "rg",
+        vec![
+            fill_color.0.into(),
+            fill_color.1.into(),
+            fill_color.2.into(),
+        ],
+    ));
+    draw_to.insert_into_page(placement_info.page_number, Operation::new("re", rec.into()));
+    draw_to.insert_into_page(placement_info.page_number, Operation::new("f", vec![]));
+
+    draw_to.insert_into_page(placement_info.page_number, Operation::new("Q", vec![]));
+}
+
 pub fn draw_horizontal_line<T:DrawInfoReq>(
     draw_to: &mut T,
     page_number: usize,
@@ -1,13 +1,15 @@
 use crate::container::container_trait::{ContainerTrait, DrawInfoReq};
 use crate::container::manager::{CurrentPlacement, Manager};
-use crate::container::page_size_info::PageSizeInfo;
 use crate::container::placement_info::PlacementInfo;
 use crate::container::rectangle::{Border, Rectangle};
 use crate::container_objects::lines::*;
 use crate::container_objects::text_box::*;
 use crate::font::font_info::FontInfo;
 use crate::font::font_sizes::Font;
+use crate::format_objects::cell_value::CellValue;
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::ops::Range;
@@ -26,6 +28,32 @@ pub enum TypeOfItem {
     String,
 }
 
+///What a footer cell shows, worked out from the column's values. Count is of the cells that aren't empty.
+#[derive(Clone)]
+pub enum Aggregate {
+    None,
+    Sum,
+    Average,
+    Count,
+    Label(String),
+}
+
+//where each row drawn comes from, data rows keep their index in the source.
+#[derive(Clone, Copy, PartialEq)]
+enum RowSlot {
+    Data(usize),
+    GroupHeader,
+    GroupFooter,
+    Footer,
+}
+
+//rows the ListBox adds itself where a page breaks.
+#[derive(Clone, Copy, PartialEq)]
+enum CarryRow {
+    CarriedForward,
+    BroughtForward,
+}
+
 #[derive(Ord, PartialOrd, Eq, PartialEq)]
 pub enum RowPositionType {
     Item,
@@ -63,10 +91,11 @@ impl<'a> ContainerTrait for ListBox<'a> {
         pdf_draw: &mut T,
         borders: &Option<RefCell<Vec<Border>>>,
     ) -> Option<PlacementInfo> {
-        if self.list_data.data.len() == 0 {
+        if self.list_data.get_slot(0).is_none() || self.list_data.next_data_index == 0 {
             return None;
         }
-        let blank_string = "".to_owned();
+        let blank_cell = CellValue::Empty;
+        self.list_data.content_width_pixels = placement_info.page_size_info.get_content_width();
 
         let (inner_border_size, outer_border_size) =
             Self::get_half_border_sizes(&self.list_data.list_box_border);
@@ -76,7 +105,7 @@ impl<'a> ContainerTrait for ListBox<'a> {
         let mut row_index = 0;
         let has_header = self.list_data.header.is_some();
         let mut dont_change_row = false;
-        let mut index_on_page = 0;
+        let mut index_on_page: usize = 0;
         //utility variables?
 
         let mut why = placement_info.rec.y;
@@ -95,28 +124,92 @@ impl<'a> ContainerTrait for ListBox<'a> {
         let mut last_connected = false;
         let mut ignore_horizontal_line = false;
         let mut ignore_next_horizontal = false;
-        while row_index < self.list_data.data.len() {
-            let is_on_last_row = row_index == self.list_data.data.len() - 1;
+        //running totals of the carry forward columns and which carry row is owed next
+        let mut carry_totals = vec![0.0; self.list_data.column_widths_percentage.len()];
+        let mut carry_pending: Option<CarryRow> = None;
+        let mut last_row_was_header = false;
+        //rows colored before the current group started, so each group starts on the first color
+        let mut zebra_start = 0;
+        //the group header that has already been moved to its own page
+        let mut new_page_slot = None;
+        //the row that starts the next page so the rows kept with it stay together
+        let mut kept_page_slot = None;
+        //cells merged over the rows under them that are still taking rows
+        let mut open_row_spans: Vec<OpenRowSpan> = Vec::new();
+        //vertical lines left out inside merged cells, the column they're before with a top and height
+        let mut hidden_lines: Vec<(usize, f64, f64)> = Vec::new();
+        while let Some((slot, row)) =
+            self.get_row_to_draw(row_index, carry_pending.is_none(), placement_info.percent_range.clone())
+        {
+            let is_on_last_row = self.list_data.get_slot(row_index + 1).is_none();
+            //a brought forward row waits for the header on the new page
+            let this_carry_row = match carry_pending {
+                Some(CarryRow::BroughtForward) if !last_row_was_header && has_header => None,
+                carry_row => carry_row,
+            };
+            let carry_row_data = this_carry_row.map(|x| self.get_carry_row(x, &carry_totals));
+            let start_new_page = this_carry_row.is_none()
+                && ((self.starts_new_page(row_index) && new_page_slot != Some(row_index))
+                    || kept_page_slot == Some(row_index));
             //row state variables for switching from header to list item.
             let mut this_row_font = self.list_data.list_item_font.clone();
-            let mut this_row_color = self.get_row_color(index_on_page); //(1.0, 1.0, 1.0);
+            let mut this_row_color = self.get_row_color(index_on_page.saturating_sub(zebra_start)); //(1.0, 1.0, 1.0);
             let mut this_text_color = (0.0, 0.0, 0.0);
             let mut this_row_is_header = false;
             let mut first_row_placement = PlacementInfo::default();
             let mut last_row_placement = PlacementInfo::default();
-            let mut column_width_total: usize = 0;
-            let mut current_row = &self.list_data.data[row_index];
+            let mut column_width_total: f64 = 0.0;
+            let mut current_row = match &carry_row_data {
+                Some(carry_row) => carry_row,
+                None => &*row,
+            };
+            let this_row_is_summary = this_carry_row.is_some()
+                || matches!(slot, RowSlot::GroupFooter | RowSlot::Footer);
+            //only data rows go under a cell merged over rows, anything else ends the merge
+            let takes_row_spans = this_carry_row.is_none()
+                && matches!(slot, RowSlot::Data(_))
+                && row.param == RowDataTypes::Normal;
+            if !takes_row_spans {
+                for span in open_row_spans.drain(..) {
+                    Self::draw_row_span(span, pdf_draw, borders);
+                }
+            }
+            let column_count = self.list_data.column_widths_percentage.len();
+            let mut row_ends_page = false;
+            //where the header's block starts inside its border, for the levels over the header
+            let mut header_top_shrink = 0.0;
 
             let mut is_left_border = false;
             let mut is_right_border = false;
 
-            for column_index in 0..self.list_data.column_widths_percentage.len() {
+            for column_index in 0..column_count {
+                let row_spans_apply = takes_row_spans && !this_row_is_header;
+                //cells inside a merged cell are drawn by the cell that covers them
+                if current_row.is_covered(column_index)
+                    || (row_spans_apply
+                        && open_row_spans
+                            .iter()
+                            .any(|x| column_index > x.columns.start && x.columns.contains(&column_index)))
+                {
+                    continue;
+                }
+                let mut row_span_index = if row_spans_apply {
+                    open_row_spans.iter().position(|x| x.columns.start == column_index)
+                } else {
+                    None
+                };
+                let mut span_columns = match row_span_index {
+                    Some(span_index) => open_row_spans[span_index].columns.len(),
+                    None => current_row.get_span(column_index).map_or(1, |x| x.columns),
+                }
+                .min(column_count - column_index);
+
                 is_left_border = false;
                 is_right_border = false;
 
                 if column_index == 0 {
                     is_left_border = true;
-                } else if column_index == self.list_data.column_widths_percentage.len() - 1 {
+                } else if column_index + span_columns == column_count {
                     is_right_border = true;
                 }
 
@@ -125,9 +218,8 @@ impl<'a> ContainerTrait for ListBox<'a> {
                 let mut current_column_start =
                     column_width_total + placement_info.percent_range.start;
                 let mut current_column_end =
-                    current_column_start + self.list_data.column_widths_percentage[column_index];
-                column_width_total =
-                    column_width_total + self.list_data.column_widths_percentage[column_index];
+                    current_column_start + self.get_columns_percent(column_index..column_index + span_columns);
+                column_width_total = current_column_end - placement_info.percent_range.start;
 
                 let is_single_row = match current_row.param {
                     RowDataTypes::SingleNoBorder(_)
@@ -137,7 +229,12 @@ impl<'a> ContainerTrait for ListBox<'a> {
                     _ => false,
                 };
 
-                let mut bpd = if is_single_row {
+                let mut bpd = if let Some(carry_row) = this_carry_row {
+                    self.get_carry_row_border_info(
+                        carry_row,
+                        current_column_start..current_column_end,
+                    )
+                } else if is_single_row {
                     current_column_start = placement_info.percent_range.start;
                     current_column_end = placement_info.percent_range.end;
                     column_width_total =
@@ -147,6 +244,7 @@ impl<'a> ContainerTrait for ListBox<'a> {
                         current_column_start..current_column_end,
                         is_start_row,
                         is_on_last_row,
+                        start_new_page,
                         &mut index_on_page,
                     );
 
@@ -155,7 +253,7 @@ impl<'a> ContainerTrait for ListBox<'a> {
                         && (bpd.row_type_and_size == RowPositionType::Header(true)
                             || bpd.row_type_and_size == RowPositionType::Header(false))
                     {
-                        column_width_total = 0;
+                        column_width_total = 0.0;
                         current_column_start =
                             column_width_total + placement_info.percent_range.start;
                         current_column_end = current_column_start
@@ -168,23 +266,67 @@ impl<'a> ContainerTrait for ListBox<'a> {
                             current_column_start..current_column_end,
                             is_start_row,
                             is_on_last_row,
+                            start_new_page,
                             &mut index_on_page,
                         )
                     } else {
                         bpd
                     }
                 } else {
-                    self.get_position_border_info(
+                    let bpd = self.get_position_border_info(
                         row_index,
                         current_column_start..current_column_end,
                         is_start_row,
                         is_on_last_row,
+                        start_new_page,
                         &mut index_on_page,
-                    )
+                    );
+                    //a header put in before the row has spans of its own
+                    let header_columns = match (&bpd.row_type_and_size, self.list_data.header) {
+                        (RowPositionType::Header(_), Some(header)) => {
+                            header.get_span(column_index).map_or(1, |x| x.columns).min(column_count - column_index)
+                        }
+                        _ => span_columns,
+                    };
+                    if let RowPositionType::Header(_) = bpd.row_type_and_size {
+                        row_span_index = None;
+                    }
+                    if header_columns != span_columns {
+                        span_columns = header_columns;
+                        is_right_border = column_index > 0 && column_index + span_columns == column_count;
+                        current_column_end =
+                            current_column_start + self.get_columns_percent(column_index..column_index + span_columns);
+                        column_width_total = current_column_end - placement_info.percent_range.start;
+                        self.get_position_border_info(
+                            row_index,
+                            current_column_start..current_column_end,
+                            is_start_row,
+                            is_on_last_row,
+                            start_new_page,
+                            &mut index_on_page,
+                        )
+                    } else {
+                        bpd
+                    }
                 };
 
+                //a data row that would end the page makes room for the carried forward row under it
+                if self.list_data.carry_forward.is_some()
+                    && this_carry_row.is_none()
+                    && !is_on_last_row
+                    && bpd.row_type_and_size == RowPositionType::Bottom
+                {
+                    bpd.row_type_and_size = if bpd.is_top_border {
+                        RowPositionType::ItemWithTopBorder
+                    } else {
+                        RowPositionType::Item
+                    };
+                    bpd.is_bottom_border = false;
+                    carry_pending = Some(CarryRow::CarriedForward);
+                }
                 if bpd.list_placement_handle.get_placement_info().is_new_page {
                     index_on_page = 0;
+                    zebra_start = 0;
                 }
                 if column_index == 0 {
                     if self.list_data.exclude_border_on_header
@@ -192,9 +334,14 @@ impl<'a> ContainerTrait for ListBox<'a> {
                         && !bpd.list_placement_handle.get_placement_info().is_new_page
                         && index_on_page > 0
                     {
-                        this_row_color = self.get_row_color(index_on_page + 1); //(1.0, 1.0, 1.0);
+                        this_row_color = self.get_row_color((index_on_page + 1).saturating_sub(zebra_start)); //(1.0, 1.0, 1.0);
                     } else {
-                        this_row_color = self.get_row_color(index_on_page); //(1.0, 1.0, 1.0);
+                        this_row_color = self.get_row_color(index_on_page.saturating_sub(zebra_start)); //(1.0, 1.0, 1.0);
+                    }
+                    if this_row_is_summary {
+                        this_row_color = self.list_data.footer_background;
+                        this_row_font = self.list_data.footer_font.clone();
+                        this_text_color = self.list_data.footer_font.font_color;
                     }
                 }
 
@@ -288,6 +435,7 @@ impl<'a> ContainerTrait for ListBox<'a> {
                             }
                         }
                         no_more_under_row_on_page = true;
+                        row_ends_page = true;
                         self.list_data.page_top = 0.0;
                     }
                     RowPositionType::ItemWithTopBorder => {
@@ -302,6 +450,7 @@ impl<'a> ContainerTrait for ListBox<'a> {
                             bpd.item_height_pixels + outer_border_size + outer_border_size,
                         );
                         no_more_under_row_on_page = true;
+                        row_ends_page = true;
                         if column_index == 0 {
                             let vs = VertSpacing::new(
                                 bpd.list_placement_handle.get_placement_info().page_number,
@@ -365,6 +514,13 @@ impl<'a> ContainerTrait for ListBox<'a> {
                         bpd.is_bottom_border,
                         no_top_border_exception,
                     );
+                //the header's own text goes under the levels over it
+                let shrink_top = if this_row_is_header && !self.list_data.header_levels.is_empty() {
+                    header_top_shrink = shrink_top;
+                    self.get_header_levels_pixels() + self.list_data.inner_border_size + 0.2
+                } else {
+                    shrink_top
+                };
                 bpd.list_placement_handle.set_restricted_interior(
                     shrink_top,
                     shrink_left,
@@ -375,14 +531,34 @@ impl<'a> ContainerTrait for ListBox<'a> {
                 let col_data = if column_index < current_row.data.len() {
                     &current_row.data[column_index]
                 } else {
-                    &blank_string
+                    &blank_cell
+                };
+                let type_of_item = match slot {
+                    _ if this_carry_row.is_some() => self.get_carry_type(column_index),
+                    RowSlot::Footer => Self::get_aggregate_type(
+                        &self.list_data.types_of_items,
+                        self.list_data.footer_aggregates.as_ref(),
+                        column_index,
+                    ),
+                    RowSlot::GroupFooter => Self::get_aggregate_type(
+                        &self.list_data.types_of_items,
+                        self.list_data.group_footer_aggregates.as_ref(),
+                        column_index,
+                    ),
+                    _ => self.list_data.types_of_items[column_index].clone(),
                 };
                 let (display_text, text_color) = Self::format_column(
-                    &self.list_data.types_of_items[column_index],
+                    &type_of_item,
                     col_data,
                     this_text_color,
                     this_row_is_header,
                 );
+                let display_text = if self.list_data.wrap_cells && !this_row_is_header && !this_row_is_summary {
+                    let width = self.get_cell_text_width(column_index..column_index + span_columns, is_single_row);
+                    TextBox::wrap_text(&display_text, &this_row_font, width)
+                } else {
+                    display_text
+                };
                 let mut font_i = this_row_font.clone();
                 let (color,align) = if let RowDataTypes::SingleWithColor(col, align) = &current_row.param {
                     (col.clone(),align.clone())
@@ -392,6 +568,10 @@ impl<'a> ContainerTrait for ListBox<'a> {
                     (text_color.clone(),text_alignment.clone())
                 };
                 font_i.font_color = color.to_owned();
+                let text_runs = match col_data {
+                    CellValue::RichText(runs) if !this_row_is_header => Some(runs.clone()),
+                    _ => None,
+                };
                 //font_i.size = 2.0;
                 let mut text_box = if this_row_is_header && self.list_data.exclude_border_on_header
                 {
@@ -413,18 +593,58 @@ impl<'a> ContainerTrait for ListBox<'a> {
                         self.list_data.group,
                     )
                 };
-                bpd.list_placement_handle
-                    .draw(&mut text_box, pdf_draw, borders);
+                if let Some(runs) = text_runs {
+                    text_box.set_runs(runs);
+                }
+                //a cell merged over rows is drawn blank until its last row is placed
+                let cell_placement = bpd.list_placement_handle.get_placement_info();
+                let row_span_rows = if takes_row_spans && !this_row_is_header {
+                    current_row.get_span(column_index).map_or(1, |x| x.rows)
+                } else {
+                    1
+                };
+                if let Some(span_index) = row_span_index {
+                    let span = &mut open_row_spans[span_index];
+                    let mut blank = TextBox::new("", this_row_font.clone(), None, None, Some(span.background), self.list_data.group);
+                    bpd.list_placement_handle.draw(&mut blank, pdf_draw, borders);
+                    if span.is_continued {
+                        span.first = cell_placement.clone();
+                        span.is_continued = false;
+                    }
+                    span.last = cell_placement.clone();
+                    span.rows_left -= 1;
+                } else if row_span_rows > 1 {
+                    let mut blank = TextBox::new("", this_row_font.clone(), None, None, Some(this_row_color), self.list_data.group);
+                    bpd.list_placement_handle.draw(&mut blank, pdf_draw, borders);
+                    open_row_spans.push(OpenRowSpan {
+                        columns: column_index..column_index + span_columns,
+                        rows_left: row_span_rows - 1,
+                        text_box,
+                        background: this_row_color,
+                        first: cell_placement.clone(),
+                        last: cell_placement.clone(),
+                        is_continued: false,
+                    });
+                } else {
+                    bpd.list_placement_handle
+                        .draw(&mut text_box, pdf_draw, borders);
+                }
+                for covered_index in column_index + 1..column_index + span_columns {
+                    hidden_lines.push((covered_index, cell_placement.rec.y, cell_placement.rec.height));
+                }
 
                 //first_row_placement, last_row_placement are to draw the horizontal line
                 if column_index == 0 {
                     first_row_placement = bpd.list_placement_handle.get_placement_info();
-                } else if column_index == self.list_data.column_widths_percentage.len() - 1 {
+                }
+                if (column_index > 0 && column_index + span_columns == column_count)
+                    || (column_index == 0 && span_columns > 1 && span_columns == column_count)
+                {
                     last_row_placement = bpd.list_placement_handle.get_placement_info();
                 }
 
                 //last placement is to draw the vertical lines and rectangle border
-                if column_index == self.list_data.column_widths_percentage.len() - 1 {
+                if column_index + span_columns == column_count {
                     last_placement = bpd.list_placement_handle.get_placement_info();
                 }
 
@@ -498,10 +718,47 @@ impl<'a> ContainerTrait for ListBox<'a> {
                 }
             }
 
+            if this_row_is_header && !self.list_data.header_levels.is_empty() {
+                let level_lines = self.draw_header_levels(
+                    &first_row_placement,
+                    &last_row_placement,
+                    header_top_shrink,
+                    this_row_color,
+                    pdf_draw,
+                    borders,
+                );
+                hidden_lines.extend(level_lines);
+            }
+
+            //merged cells are drawn over the lines between the rows they cover, one taller than
+            //what's left of the page carries on blank over its rows on the next page
+            let mut continued_row_spans = Vec::new();
+            for span in open_row_spans.iter_mut() {
+                if row_ends_page && !is_on_last_row && span.rows_left > 0 {
+                    let text_box = TextBox::new("", this_row_font.clone(), None, None, Some(span.background), self.list_data.group);
+                    continued_row_spans.push(OpenRowSpan {
+                        columns: span.columns.clone(),
+                        rows_left: span.rows_left,
+                        text_box,
+                        background: span.background,
+                        first: span.first.clone(),
+                        last: span.last.clone(),
+                        is_continued: true,
+                    });
+                }
+                if row_ends_page || is_on_last_row {
+                    span.rows_left = 0;
+                }
+            }
+            while let Some(span_index) = open_row_spans.iter().position(|x| x.rows_left == 0) {
+                Self::draw_row_span(open_row_spans.remove(span_index), pdf_draw, borders);
+            }
+            open_row_spans.extend(continued_row_spans);
+
             if no_more_under_row_on_page {
                 //draw vertical lines
                 for lines in line_spaces.clone() {
-                    let mut column_width_total: usize = 0;
+                    let mut column_width_total: f64 = 0.0;
 
                     for ci in 0..self.list_data.column_widths_percentage.len() {
                         let current_column_start =
@@ -510,43 +767,91 @@ impl<'a> ContainerTrait for ListBox<'a> {
                             column_width_total + self.list_data.column_widths_percentage[ci];
 
                         if ci > 0 {
-                            match self.list_data.list_box_border {
-                                ListBoxBorder::All(inner_size, _) => {
-                                    draw_vertical_line(
-                                        pdf_draw,
-                                        last_row_placement.page_number,
-                                        lines.0, // putting -0.5 got rid of artifact
-                                        current_column_start as f64 / 100.0
-                                            * self.manager.get_page_pixel_dims().0,
-                                        lines.1,
-                                        last_row_placement.page_size_info.page_height_pixels,
-                                        inner_size,
-                                        self.list_data.border_color,
-                                    );
-                                }
-                                ListBoxBorder::Inner(inner_size) => {
-                                    draw_vertical_line(
-                                        pdf_draw,
-                                        last_row_placement.page_number,
-                                        lines.0, // putting -0.5 got rid of artifact
-                                        current_column_start as f64 / 100.0
-                                            * self.manager.get_page_pixel_dims().0,
-                                        lines.1,
-                                        last_row_placement.page_size_info.page_height_pixels,
-                                        inner_size,
-                                        self.list_data.border_color,
-                                    );
+                            let hidden = hidden_lines.iter().filter(|x| x.0 == ci).map(|x| (x.1, x.2));
+                            for (top, height) in Self::get_visible_parts(lines.0, lines.1, hidden) {
+                                match self.list_data.list_box_border {
+                                    ListBoxBorder::All(inner_size, _) => {
+                                        draw_vertical_line(
+                                            pdf_draw,
+                                            last_row_placement.page_number,
+                                            top, // putting -0.5 got rid of artifact
+                                            last_row_placement.page_size_info.get_x_for_percent(
+                                                last_row_placement.page_number,
+                                                current_column_start,
+                                            ),
+                                            height,
+                                            last_row_placement.page_size_info.page_height_pixels,
+                                            inner_size,
+                                            self.list_data.border_color,
+                                        );
+                                    }
+                                    ListBoxBorder::Inner(inner_size) => {
+                                        draw_vertical_line(
+                                            pdf_draw,
+                                            last_row_placement.page_number,
+                                            top, // putting -0.5 got rid of artifact
+                                            last_row_placement.page_size_info.get_x_for_percent(
+                                                last_row_placement.page_number,
+                                                current_column_start,
+                                            ),
+                                            height,
+                                            last_row_placement.page_size_info.page_height_pixels,
+                                            inner_size,
+                                            self.list_data.border_color,
+                                        );
+                                    }
+                                    _ => {}
                                 }
-                                _ => {}
                             }
                         }
                     }
                     line_spaces = Vec::new();
                 }
+                hidden_lines.clear();
             }
 
+            last_row_was_header = this_row_is_header;
+            if this_carry_row.is_none() && self.starts_new_page(row_index) {
+                new_page_slot = Some(row_index);
+            }
+            if kept_page_slot == Some(row_index) {
+                kept_page_slot = None;
+            }
+            //a page that ended before the next row's kept rows would fit doesn't take that row
+            if row_ends_page
+                && this_carry_row.is_none()
+                && carry_pending.is_none()
+                && !dont_change_row
+                && self.list_data.get_slot(row_index + 1).is_some()
+            {
+                let next_row_pixels = self.get_row_height_pixels(row_index + 1);
+                if self.get_kept_pixels(row_index + 1, next_row_pixels) > 0.0 {
+                    kept_page_slot = Some(row_index + 1);
+                }
+            }
+            if let RowSlot::GroupHeader = slot {
+                if self.list_data.restart_zebra_per_group && !this_row_is_header && this_carry_row.is_none() {
+                    zebra_start = index_on_page + 1;
+                }
+            }
+            match this_carry_row {
+                Some(CarryRow::CarriedForward) => {
+                    dont_change_row = true;
+                    carry_pending = Some(CarryRow::BroughtForward);
+                }
+                Some(CarryRow::BroughtForward) => {
+                    dont_change_row = true;
+                    carry_pending = None;
+                }
+                None => {}
+            }
             if !dont_change_row {
+                if let RowSlot::Data(_) = slot {
+                    Self::add_to_carry_totals(&mut carry_totals, &row);
+                }
                 row_index = row_index + 1;
+                //drawn rows aren't looked at again
+                self.list_data.release_slots_before(row_index);
             }
             dont_change_row = false;
 
@@ -560,16 +865,20 @@ impl<'a> ContainerTrait for ListBox<'a> {
                     if self.list_data.list_box_page_height_info[i].top > 0.0000001
                         && self.list_data.list_box_page_height_info[i].bottom > 0.00000001
                     {
+                        let page_size_info = self
+                            .manager
+                            .get_page_size_info(self.list_data.list_box_page_height_info[i].page);
                         let pl = PlacementInfo {
                             rec: Rectangle::new(
-                                placement_info.percent_range.start as f64 / 100.0
-                                    * self.manager.get_page_pixel_dims().0,
+                                page_size_info.get_x_for_percent(
+                                    self.list_data.list_box_page_height_info[i].page,
+                                    placement_info.percent_range.start,
+                                ),
                                 self.list_data.list_box_page_height_info[i].top,
                                 (placement_info.percent_range.end
                                     - placement_info.percent_range.start)
-                                    as f64
                                     / 100.0
-                                    * self.manager.get_page_pixel_dims().0,
+                                    * page_size_info.get_content_width(),
                                 self.list_data.list_box_page_height_info[i].bottom
                                     - self.list_data.list_box_page_height_info[i].top,
                             ),
@@ -579,13 +888,8 @@ impl<'a> ContainerTrait for ListBox<'a> {
                             page_number: self.list_data.list_box_page_height_info[i].page,
                             percent_range: placement_info.percent_range.clone(),
                             restricted_area_option: None,
-                            page_size_info: PageSizeInfo::new(
-                                last_placement.page_size_info.page_width_pixels,
-                                last_placement.page_size_info.page_height_pixels,
-                                last_placement.page_size_info.dpi,
-                                last_placement.page_size_info.top_margin,
-                                last_placement.page_size_info.bottom_margin,
-                            ),
+                            page_layouts: placement_info.page_layouts.clone(),
+                            page_size_info,
                         };
                         match borders {
                             None => {
@@ -609,6 +913,40 @@ impl<'a> ContainerTrait for ListBox<'a> {
             }
             _ => {}
         }
+
+        //named columns cover the table's part of the last page it is on
+        if let Some(last_page) = self.list_data.list_box_page_height_info.last() {
+            let page_size_info = self.manager.get_page_size_info(last_page.page);
+            for (column_index, name) in &self.list_data.column_names {
+                if *column_index >= self.list_data.column_widths_percentage.len() {
+                    continue;
+                }
+                let column_start = placement_info.percent_range.start
+                    + self.list_data.column_widths_percentage[..*column_index]
+                        .iter()
+                        .sum::<f64>();
+                let column_end =
+                    column_start + self.list_data.column_widths_percentage[*column_index];
+                let column_placement = PlacementInfo {
+                    rec: Rectangle::new(
+                        page_size_info.get_x_for_percent(last_page.page, column_start),
+                        last_page.top,
+                        page_size_info.get_content_width() * (column_end - column_start) / 100.0,
+                        last_page.bottom - last_page.top,
+                    ),
+                    is_new_page: false,
+                    draw_height_left_on_page: page_size_info.page_height_pixels
+                        - page_size_info.bottom_margin
+                        - last_page.bottom,
+                    page_number: last_page.page,
+                    percent_range: column_start..column_end,
+                    restricted_area_option: None,
+                    page_layouts: placement_info.page_layouts.clone(),
+                    page_size_info: page_size_info.clone(),
+                };
+                self.manager.name_placement(name, column_placement);
+            }
+        }
         Some(last_placement)
     }
 
@@ -616,7 +954,7 @@ impl<'a> ContainerTrait for ListBox<'a> {
         self.list_data.group
     }
 }
-#[derive(PartialOrd, PartialEq)]
+#[derive(Clone, PartialOrd, PartialEq)]
 pub enum RowDataTypes {
     Normal,
     Single(TextAlignment),
@@ -629,15 +967,135 @@ impl Default for RowDataTypes {
         RowDataTypes::Normal
     }
 }
+///A cell that covers the columns after it and the rows under it, what's in the covered cells isn't shown.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CellSpan {
+    pub column: usize,
+    pub columns: usize,
+    pub rows: usize,
+}
+impl CellSpan {
+    fn covers(&self, column_index: usize) -> bool {
+        column_index > self.column && column_index < self.column + self.columns
+    }
+}
+#[derive(Clone)]
 pub struct RowData {
-    pub data: Vec<String>,
+    pub data: Vec<CellValue>,
     pub param: RowDataTypes,
+    pub keep_with_next: usize,
+    pub spans: Vec<CellSpan>,
 }
 impl RowData {
-    pub fn new(data: Vec<String>, param: RowDataTypes) -> Self {
-        Self { data, param }
+    pub fn new<C: Into<CellValue>>(data: Vec<C>, param: RowDataTypes) -> Self {
+        Self {
+            data: data.into_iter().map(Into::into).collect(),
+            param,
+            keep_with_next: 0,
+            spans: Vec::new(),
+        }
+    }
+    ///Number of rows after this one that have to land on the same page, used so a
+    ///section title is never left alone at the bottom of a page.
+    pub fn set_keep_with_next(&mut self, rows: usize) {
+        self.keep_with_next = rows;
+    }
+    ///Merges the cell at column with the columns after it and the rows under it, like a
+    ///description across three columns or one label beside a few rows. Only normal rows span.
+    ///The rows are kept on one page, a merge taller than a page carries on blank on the next.
+    pub fn set_span(&mut self, column: usize, columns: usize, rows: usize) {
+        self.spans.retain(|x| x.column != column);
+        self.spans.push(CellSpan {
+            column,
+            columns: columns.max(1),
+            rows: rows.max(1),
+        });
+    }
+    //the span that starts at column.
+    fn get_span(&self, column_index: usize) -> Option<&CellSpan> {
+        if self.param != RowDataTypes::Normal {
+            return None;
+        }
+        self.spans.iter().find(|x| x.column == column_index)
+    }
+    //whether column is inside a span that starts before it.
+    fn is_covered(&self, column_index: usize) -> bool {
+        self.param == RowDataTypes::Normal && self.spans.iter().any(|x| x.covers(column_index))
     }
 }
+pub use backfat_derive::ListRow;
+
+///A struct that makes ListBox rows, usually through #[derive(ListRow)], and knows the header,
+///widths, types and alignments of its columns.
+pub trait ListRow {
+    fn to_row(&self) -> RowData;
+    fn header() -> RowData;
+    ///Widths in percent of the page for a table placed in a range range_percent of the page wide.
+    fn column_widths(range_percent: f64) -> Vec<f64>;
+    fn column_types() -> Vec<TypeOfItem>;
+    fn column_alignments() -> Vec<TextAlignment>;
+}
+
+///Where a ListBox reads its rows from. Rows are read once, in order, as the table is drawn and
+///only the few the layout looks ahead at are kept, so a stream can be as long as it needs to be.
+pub struct RowSource<'a> {
+    rows: Box<dyn Iterator<Item = Cow<'a, RowData>> + 'a>,
+}
+impl<'a> RowSource<'a> {
+    ///Rows made as they're needed, like from a database cursor.
+    pub fn stream<I>(rows: I) -> Self
+    where
+        I: IntoIterator<Item = RowData>,
+        I::IntoIter: 'a,
+    {
+        Self {
+            rows: Box::new(rows.into_iter().map(Cow::Owned)),
+        }
+    }
+}
+impl<'a> From<&'a Vec<RowData>> for RowSource<'a> {
+    fn from(rows: &'a Vec<RowData>) -> Self {
+        Self {
+            rows: Box::new(rows.iter().map(Cow::Borrowed)),
+        }
+    }
+}
+//running values of each column for the footers, so the rows don't have to be kept.
+#[derive(Clone, Default)]
+struct ColumnTotals {
+    sums: Vec<f64>,
+    numbers: Vec<usize>,
+    non_empty: Vec<usize>,
+}
+impl ColumnTotals {
+    fn add(&mut self, row: &RowData) {
+        if self.sums.len() < row.data.len() {
+            self.sums.resize(row.data.len(), 0.0);
+            self.numbers.resize(row.data.len(), 0);
+            self.non_empty.resize(row.data.len(), 0);
+        }
+        for (column_index, cell) in row.data.iter().enumerate() {
+            if let Some(value) = cell.as_f64() {
+                self.sums[column_index] += value;
+                self.numbers[column_index] += 1;
+            }
+            if !cell.is_empty() {
+                self.non_empty[column_index] += 1;
+            }
+        }
+    }
+}
+//a cell merged over the rows under it, drawn again across all of them once the last is placed.
+struct OpenRowSpan {
+    columns: Range<usize>,
+    rows_left: usize,
+    text_box: TextBox,
+    background: (f64, f64, f64),
+    first: PlacementInfo,
+    last: PlacementInfo,
+    //carried over from the page before, it starts at the first row it takes on this page
+    is_continued: bool,
+}
 struct VertSpacing {
     page: usize,
     top: f64,
@@ -648,9 +1106,28 @@ impl VertSpacing {
         Self { page, top, bottom }
     }
 }
+//columns whose running totals are shown where a page breaks, and the rows' labels.
+struct CarryForward {
+    columns: Vec<usize>,
+    carried_label: String,
+    brought_label: String,
+}
+//the key a row is grouped by.
+type GroupKey<'a> = Box<dyn Fn(&RowData) -> String + 'a>;
+//height in pixels of a row, worked out when it's reached.
+type RowHeight<'a> = Box<dyn Fn(&RowData) -> f64 + 'a>;
+
 struct ListData<'a> {
-    data: &'a Vec<RowData>,
-    column_widths_percentage: Vec<usize>,
+    rows: RowSource<'a>,
+    //rows read from the source and not drawn yet, with the group rows and footer they bring
+    slots: VecDeque<(RowSlot, Cow<'a, RowData>)>,
+    first_slot_index: usize,
+    next_data_index: usize,
+    rows_finished: bool,
+    group_totals: Option<(String, ColumnTotals)>,
+    footer_totals: ColumnTotals,
+    row_height: Option<RowHeight<'a>>,
+    column_widths_percentage: Vec<f64>,
     header: Option<&'a RowData>,
     header_column_text_alignments: Option<Vec<TextAlignment>>,
     alternate_row_colors: Option<((f64, f64, f64), (f64, f64, f64))>,
@@ -663,16 +1140,117 @@ struct ListData<'a> {
     list_item_font: FontInfo,
     row_cell_size: Option<Vec<f64>>,
     row_header_pixels: f64,
+    //height of one line of the header, the block is taller with levels over it
+    header_line_pixels: f64,
+    header_levels: Vec<&'a RowData>,
     inner_border_size: f64,
     outer_border_size: f64,
     group: Option<usize>,
     list_box_page_height_info: Vec<VertSpacing>,
     page_top: f64,
+    column_names: Vec<(usize, String)>,
+    footer_aggregates: Option<Vec<Aggregate>>,
+    footer_font: FontInfo,
+    footer_background: (f64, f64, f64),
+    carry_forward: Option<CarryForward>,
+    group_by: Option<GroupKey<'a>>,
+    group_header_style: ((f64, f64, f64), TextAlignment),
+    group_footer_aggregates: Option<Vec<Aggregate>>,
+    restart_zebra_per_group: bool,
+    new_page_per_group: bool,
+    wrap_cells: bool,
+    split_tall_rows: bool,
+    //width the column percentages are of, known once the list box is placed
+    content_width_pixels: f64,
+}
+impl<'a> ListData<'a> {
+    //the row at index, reading ahead in the source as far as needed.
+    fn get_slot(&mut self, index: usize) -> Option<&(RowSlot, Cow<'a, RowData>)> {
+        while index >= self.first_slot_index + self.slots.len() {
+            if !self.read_next_row() {
+                return None;
+            }
+        }
+        self.peek_slot(index)
+    }
+    //the row at index if it's already been read.
+    fn peek_slot(&self, index: usize) -> Option<&(RowSlot, Cow<'a, RowData>)> {
+        index
+            .checked_sub(self.first_slot_index)
+            .and_then(|x| self.slots.get(x))
+    }
+    //whether the row at index is what's left of the row before it.
+    fn is_split_at(&self, index: usize) -> bool {
+        match (index.checked_sub(1).and_then(|x| self.peek_slot(x)), self.peek_slot(index)) {
+            (Some((RowSlot::Data(before), _)), Some((RowSlot::Data(after), _))) => before == after,
+            _ => false,
+        }
+    }
+    //puts first in place of the row at index and rest right after it.
+    fn split_slot(&mut self, index: usize, first: RowData, rest: RowData) {
+        let queue_index = index - self.first_slot_index;
+        let slot = self.slots[queue_index].0;
+        self.slots[queue_index].1 = Cow::Owned(first);
+        self.slots.insert(queue_index + 1, (slot, Cow::Owned(rest)));
+    }
+    fn release_slots_before(&mut self, index: usize) {
+        while self.first_slot_index < index && self.slots.pop_front().is_some() {
+            self.first_slot_index += 1;
+        }
+    }
+    //queues the next source row with any group rows it brings, or the closing rows at the end.
+    fn read_next_row(&mut self) -> bool {
+        if self.rows_finished {
+            return false;
+        }
+        let row = match self.rows.rows.next() {
+            Some(row) => row,
+            None => {
+                self.rows_finished = true;
+                self.close_group();
+                if let Some(aggregates) = &self.footer_aggregates {
+                    let footer = ListBox::get_aggregate_row(aggregates, &self.footer_totals, "");
+                    self.slots.push_back((RowSlot::Footer, Cow::Owned(footer)));
+                }
+                return true;
+            }
+        };
+        if row.param == RowDataTypes::Normal {
+            if let Some(group_by) = &self.group_by {
+                let key = group_by(&row);
+                if self.group_totals.as_ref().map(|x| &x.0) != Some(&key) {
+                    self.close_group();
+                    let (font_color, alignment) = self.group_header_style.clone();
+                    let group_header = RowData::new(
+                        vec![key.clone()],
+                        RowDataTypes::SingleWithColor(font_color, alignment),
+                    );
+                    self.slots.push_back((RowSlot::GroupHeader, Cow::Owned(group_header)));
+                    self.group_totals = Some((key, ColumnTotals::default()));
+                }
+                if let Some((_, totals)) = self.group_totals.as_mut() {
+                    totals.add(&row);
+                }
+            }
+            self.footer_totals.add(&row);
+        }
+        self.slots.push_back((RowSlot::Data(self.next_data_index), row));
+        self.next_data_index += 1;
+        true
+    }
+    fn close_group(&mut self) {
+        if let (Some((key, totals)), Some(aggregates)) =
+            (self.group_totals.take(), &self.group_footer_aggregates)
+        {
+            let group_footer = ListBox::get_aggregate_row(aggregates, &totals, &key);
+            self.slots.push_back((RowSlot::GroupFooter, Cow::Owned(group_footer)));
+        }
+    }
 }
 impl<'a> ListBox<'a> {
-    pub fn new(
-        data: &'a Vec<RowData>,
-        column_widths_percentage: Vec<usize>,
+    pub fn new<R: Into<RowSource<'a>>>(
+        data: R,
+        column_widths_percentage: Vec<f64>,
         header: Option<&'a RowData>,
         manager: &'a mut Manager,
         list_item_font: FontInfo,
@@ -700,9 +1278,18 @@ impl<'a> ListBox<'a> {
         }
 
         let col_wdth = column_widths_percentage.len();
+        let placement_page_width = manager.get_page_size_info(0).get_content_width();
+        let footer_font = list_item_font.clone();
         Self {
             list_data: ListData {
-                data,
+                rows: data.into(),
+                slots: VecDeque::new(),
+                first_slot_index: 0,
+                next_data_index: 0,
+                rows_finished: false,
+                group_totals: None,
+                footer_totals: ColumnTotals::default(),
+                row_height: None,
                 column_widths_percentage,
                 header,
                 header_column_text_alignments: None,
@@ -716,11 +1303,26 @@ impl<'a> ListBox<'a> {
                 list_item_font,
                 row_cell_size: None,
                 row_header_pixels,
+                header_line_pixels: row_header_pixels,
+                header_levels: Vec::new(),
                 inner_border_size,
                 outer_border_size,
                 group,
                 list_box_page_height_info: vec![],
                 page_top: 0.0,
+                column_names: vec![],
+                footer_aggregates: None,
+                footer_font,
+                footer_background: (0.85, 0.85, 0.85),
+                carry_forward: None,
+                group_by: None,
+                group_header_style: ((0.0, 0.0, 0.0), TextAlignment::LeftCenter),
+                group_footer_aggregates: None,
+                restart_zebra_per_group: false,
+                new_page_per_group: false,
+                wrap_cells: false,
+                split_tall_rows: false,
+                content_width_pixels: placement_page_width,
             },
             manager,
         }
@@ -730,22 +1332,23 @@ impl<'a> ListBox<'a> {
     fn get_item_height_pixels(&mut self, row_index: usize) -> (f64, f64) {
         let (_, page_height, _) = self.manager.get_page_pixel_dims();
         let margins = self.manager.get_page_pixel_margins();
-        let (mut item_height_pixels, mut next_juan) = match &self.list_data.row_cell_size {
-            Some(row_sizes) => (
-                row_sizes[row_index],
-                row_sizes
-                    .get(row_index + 1)
-                    .unwrap_or(&row_sizes[row_index])
-                    .to_owned() as f64,
-            ),
-            None => {
-                let mut item_height_pixels = 0.0159708658854167
-                    * self.list_data.list_item_font.size
-                    * self.manager.get_page_pixel_dims().2; //13.798828125;//get_font_breakdown(self.list_data.list_item_font.size).2 * self.manager.get_page_pixel_dims().2 * 1.2;
-                item_height_pixels = item_height_pixels * 1.2;
-
-                (item_height_pixels, item_height_pixels)
+        let mut item_height_pixels = self.get_row_height_pixels(row_index);
+        let mut next_juan = if self.starts_new_page(row_index + 1) {
+            //never fits, so this row closes the page
+            f64::INFINITY
+        } else if self.list_data.get_slot(row_index + 1).is_some() {
+            let next_row_pixels = self.get_row_height_pixels(row_index + 1);
+            if self.list_data.split_tall_rows
+                && self.is_wrapped_height(row_index + 1)
+                && (next_row_pixels > self.get_max_row_pixels() || self.list_data.is_split_at(row_index + 1))
+            {
+                //it gets split or is the rest of this row, either way it can start on the next page
+                self.get_item_font_pixels()
+            } else {
+                next_row_pixels
             }
+        } else {
+            item_height_pixels
         };
 
         if item_height_pixels
@@ -774,24 +1377,439 @@ impl<'a> ListBox<'a> {
                 - self.list_data.outer_border_size * 2.0
                 - self.list_data.inner_border_size * 2.0;
         }
+        next_juan += self.get_kept_pixels(row_index + 1, next_juan);
         (item_height_pixels, next_juan)
     }
 
+    //the rows kept with this one, as long as they would all fit on a fresh page with it.
+    fn get_kept_pixels(&mut self, row_index: usize, row_pixels: f64) -> f64 {
+        let (_, page_height, _) = self.manager.get_page_pixel_dims();
+        let margins = self.manager.get_page_pixel_margins();
+        let kept_pixels = self.get_keep_with_next_pixels(row_index);
+        if row_pixels + kept_pixels
+            <= page_height
+                - margins.top_margin_pixels
+                - margins.bottom_margin_pixels
+                - self.list_data.row_header_pixels
+                - self.list_data.outer_border_size * 4.0
+        {
+            kept_pixels
+        } else {
+            0.0
+        }
+    }
+
+    fn get_item_font_pixels(&self) -> f64 {
+        0.0159708658854167 * self.list_data.list_item_font.size * self.manager.get_page_pixel_dims().2 * 1.2
+    }
+
+    fn get_row_height_pixels(&mut self, row_index: usize) -> f64 {
+        let item_font_pixels = self.get_item_font_pixels();
+        let summary_row_pixels = self.get_summary_row_pixels();
+        self.list_data.get_slot(row_index);
+        let list_data = &self.list_data;
+        match list_data.peek_slot(row_index) {
+            Some((RowSlot::Data(data_index), row)) => match (&list_data.row_height, &list_data.row_cell_size) {
+                (Some(row_height), _) => row_height(row),
+                (None, Some(row_sizes)) if *data_index < row_sizes.len() => row_sizes[*data_index],
+                _ if list_data.wrap_cells => {
+                    //each line past the first adds a line of the font
+                    let line_count = self.get_wrapped_line_count(row);
+                    item_font_pixels
+                        + (line_count - 1) as f64 * self.list_data.list_item_font.size * 1.2
+                }
+                _ => item_font_pixels,
+            },
+            Some((RowSlot::GroupHeader, _)) => item_font_pixels,
+            _ => summary_row_pixels,
+        }
+    }
+
+    //lines in the row's tallest cell once the cells are wrapped to their columns.
+    fn get_wrapped_line_count(&self, row: &RowData) -> usize {
+        self.get_wrapped_cells(row)
+            .iter()
+            .map(|x| x.len())
+            .max()
+            .unwrap_or(1)
+            .max(1)
+    }
+    //the lines of each cell, a single row only has the one cell across the list box. Covered
+    //cells have none and neither do cells merged over rows, they fit in the rows they cover.
+    fn get_wrapped_cells(&self, row: &RowData) -> Vec<Vec<String>> {
+        let is_single_row = row.param != RowDataTypes::Normal;
+        let column_count = self.list_data.column_widths_percentage.len();
+        (0..column_count)
+            .take(if is_single_row { 1 } else { usize::MAX })
+            .map(|column_index| {
+                let span_columns = match row.get_span(column_index) {
+                    Some(span) if span.rows > 1 => return Vec::new(),
+                    Some(span) => span.columns.min(column_count - column_index),
+                    None if row.is_covered(column_index) => return Vec::new(),
+                    None => 1,
+                };
+                let value = row.data.get(column_index).unwrap_or(&CellValue::Empty);
+                let (text, _) = Self::format_column(
+                    &self.list_data.types_of_items[column_index],
+                    value,
+                    (0.0, 0.0, 0.0),
+                    false,
+                );
+                let width = self.get_cell_text_width(column_index..column_index + span_columns, is_single_row);
+                TextBox::wrap_text(&text, &self.list_data.list_item_font, width)
+                    .split('\n')
+                    .map(|x| x.to_string())
+                    .collect()
+            })
+            .collect()
+    }
+    //tallest a row can be under the header on a fresh page.
+    fn get_max_row_pixels(&self) -> f64 {
+        let (_, page_height, _) = self.manager.get_page_pixel_dims();
+        let margins = self.manager.get_page_pixel_margins();
+        page_height
+            - margins.top_margin_pixels
+            - margins.bottom_margin_pixels
+            - self.list_data.row_header_pixels
+            - self.list_data.outer_border_size * 4.0
+            - self.list_data.inner_border_size * 4.0
+            - self.get_carry_reserve_pixels()
+    }
+    //room kept at the bottom of a page for a carried forward row.
+    fn get_carry_reserve_pixels(&self) -> f64 {
+        if self.list_data.carry_forward.is_some() {
+            self.get_summary_row_pixels() + self.list_data.inner_border_size * 2.0
+        } else {
+            0.0
+        }
+    }
+    //whether the row's height comes from its wrapped cells rather than a height it was given.
+    fn is_wrapped_height(&self, row_index: usize) -> bool {
+        let list_data = &self.list_data;
+        match list_data.peek_slot(row_index) {
+            Some((RowSlot::Data(data_index), row)) => {
+                list_data.wrap_cells
+                    && row.param == RowDataTypes::Normal
+                    && list_data.row_height.is_none()
+                    && list_data.row_cell_size.as_ref().filter(|x| *data_index < x.len()).is_none()
+            }
+            _ => false,
+        }
+    }
+    //the next row to draw, splitting it first when it's taller than any page can hold.
+    fn get_row_to_draw(
+        &mut self,
+        row_index: usize,
+        can_split: bool,
+        range: Range<f64>,
+    ) -> Option<(RowSlot, Cow<'a, RowData>)> {
+        if can_split && self.list_data.split_tall_rows {
+            self.split_tall_row(row_index, range);
+        }
+        self.list_data.get_slot(row_index).cloned()
+    }
+    //the part of the row that fits where it would be drawn stays, the rest becomes the next row.
+    fn split_tall_row(&mut self, row_index: usize, range: Range<f64>) {
+        let max_row_pixels = self.get_max_row_pixels();
+        self.list_data.get_slot(row_index);
+        if !self.is_wrapped_height(row_index) || self.get_row_height_pixels(row_index) <= max_row_pixels {
+            return;
+        }
+        let row = match self.list_data.peek_slot(row_index) {
+            Some((_, row)) => row.clone().into_owned(),
+            None => return,
+        };
+        let item_font_pixels = self.get_item_font_pixels();
+        let line_pixels = self.list_data.list_item_font.size * 1.2;
+        let placement = self.manager.get_placement_handle(range, false).get_placement_info();
+        let mut room = placement.draw_height_left_on_page
+            - self.list_data.outer_border_size
+            - self.list_data.inner_border_size * 2.0
+            - self.get_carry_reserve_pixels();
+        if placement.is_new_page || room < item_font_pixels || room > max_row_pixels {
+            room = max_row_pixels;
+        }
+        let lines_that_fit = ((room - item_font_pixels) / line_pixels).floor().max(0.0) as usize + 1;
+        let mut first = Vec::new();
+        let mut rest = Vec::new();
+        for (column_index, lines) in self.get_wrapped_cells(&row).into_iter().enumerate() {
+            //only plain text carries on, numbers and styled text stay whole in the first part
+            let is_text = match (&self.list_data.types_of_items[column_index], row.data.get(column_index)) {
+                (TypeOfItem::String, Some(CellValue::RichText(_))) => false,
+                (TypeOfItem::String, _) => true,
+                _ => false,
+            };
+            if is_text && lines.len() > lines_that_fit {
+                first.push(CellValue::Text(lines[..lines_that_fit].join("\n")));
+                rest.push(CellValue::Text(lines[lines_that_fit..].join("\n")));
+            } else {
+                first.push(row.data.get(column_index).cloned().unwrap_or_default());
+                rest.push(CellValue::Empty);
+            }
+        }
+        //nothing would carry on, the row is cut off at the page instead
+        if rest.iter().all(|x| x.is_empty()) {
+            return;
+        }
+        //both parts keep the columns merged, a merge over rows doesn't survive the split
+        let mut first = RowData::new(first, RowDataTypes::Normal);
+        let mut rest = RowData::new(rest, RowDataTypes::Normal);
+        for span in row.spans.iter() {
+            first.set_span(span.column, span.columns, 1);
+            rest.set_span(span.column, span.columns, 1);
+        }
+        rest.set_keep_with_next(row.keep_with_next);
+        self.list_data.split_slot(row_index, first, rest);
+    }
+    //room for text across a cell and the columns it spans, inside its borders.
+    fn get_cell_text_width(&self, columns: Range<usize>, is_single_row: bool) -> f64 {
+        let percent = if is_single_row {
+            self.list_data.column_widths_percentage.iter().sum()
+        } else {
+            self.get_columns_percent(columns)
+        };
+        let border = self.list_data.inner_border_size.max(self.list_data.outer_border_size);
+        self.list_data.content_width_pixels * percent / 100.0 - (border + 0.2) * 2.0
+    }
+
+    fn get_columns_percent(&self, columns: Range<usize>) -> f64 {
+        self.list_data.column_widths_percentage[columns].iter().sum()
+    }
+
+    //what's left of a line from top down height once the hidden parts are taken out.
+    fn get_visible_parts<I: Iterator<Item = (f64, f64)>>(top: f64, height: f64, hidden: I) -> Vec<(f64, f64)> {
+        let mut hidden: Vec<(f64, f64)> = hidden.collect();
+        hidden.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        let mut parts = Vec::new();
+        let mut start = top;
+        for (hidden_top, hidden_height) in hidden {
+            if hidden_top > start {
+                parts.push((start, hidden_top.min(top + height) - start));
+            }
+            start = start.max(hidden_top + hidden_height);
+        }
+        if start == top {
+            parts.push((top, height));
+        } else if start < top + height {
+            parts.push((start, top + height - start));
+        }
+        parts.retain(|x| x.1 > 0.000001);
+        parts
+    }
+
+    //draws a cell merged over rows across all of them, covering the cells and lines under it.
+    fn draw_row_span<T: DrawInfoReq>(
+        span: OpenRowSpan,
+        pdf_draw: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) {
+        let OpenRowSpan {
+            mut text_box,
+            first,
+            last,
+            ..
+        } = span;
+        let mut placement_info = first.clone();
+        placement_info.rec.height = last.rec.y + last.rec.height - first.rec.y;
+        if let (Some(first_inner), Some(last_inner)) =
+            (first.restricted_area_option, last.restricted_area_option)
+        {
+            placement_info.restricted_area_option = Some(Rectangle::new(
+                first_inner.x,
+                first_inner.y,
+                first_inner.width,
+                last_inner.y + last_inner.height - first_inner.y,
+            ));
+        }
+        text_box.on_draw(placement_info, pdf_draw, borders);
+    }
+
+    //from the top of the header's block to the top of the header under its levels.
+    fn get_header_levels_pixels(&self) -> f64 {
+        let level_count = self.list_data.header_levels.len() as f64;
+        self.list_data.outer_border_size - self.list_data.inner_border_size
+            + (self.list_data.header_line_pixels + self.list_data.inner_border_size * 2.0) * level_count
+    }
+
+    //draws the levels over the header in the top of its block, first the lines between them and
+    //then the cells, so a cell reaching down over a level covers the line above that level. Gives
+    //back the vertical lines the cells cover.
+    fn draw_header_levels<T: DrawInfoReq>(
+        &self,
+        first: &PlacementInfo,
+        last: &PlacementInfo,
+        top_shrink: f64,
+        background: (f64, f64, f64),
+        pdf_draw: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Vec<(usize, f64, f64)> {
+        let mut hidden_lines = Vec::new();
+        let column_count = self.list_data.column_widths_percentage.len();
+        let level_count = self.list_data.header_levels.len();
+        let level_pixels = self.list_data.header_line_pixels + self.list_data.inner_border_size * 2.0;
+        let header_top = first.rec.y + self.get_header_levels_pixels();
+        let block_bottom = first.rec.y + first.rec.height;
+        let band_top = |level: usize| {
+            if level == 0 {
+                first.rec.y
+            } else {
+                header_top - (level_count - level) as f64 * level_pixels
+            }
+        };
+        let cell_margin = self.list_data.inner_border_size + 0.2;
+        let right = last.rec.x + last.rec.width;
+        let inner_left = first.restricted_area_option.map_or(first.rec.x + cell_margin, |x| x.x);
+        let inner_right = last.restricted_area_option.map_or(right - cell_margin, |x| x.x + x.width);
+        let inner_bottom = first
+            .restricted_area_option
+            .map_or(block_bottom - cell_margin, |x| x.y + x.height);
+
+        if let ListBoxBorder::All(inner_size, _) | ListBoxBorder::Inner(inner_size) = self.list_data.list_box_border {
+            for level in 1..=level_count {
+                draw_horizontal_line(
+                    pdf_draw,
+                    first.page_number,
+                    band_top(level),
+                    first.rec.x,
+                    right - first.rec.x,
+                    first.page_size_info.page_height_pixels,
+                    inner_size,
+                    self.list_data.border_color,
+                );
+            }
+        }
+        //levels under a cell that reaches down, per column
+        let mut covered_levels = vec![0; column_count];
+        for (level, row) in self.list_data.header_levels.iter().enumerate() {
+            for column_index in 0..column_count {
+                if covered_levels[column_index] > 0 {
+                    covered_levels[column_index] -= 1;
+                    continue;
+                }
+                if row.is_covered(column_index) {
+                    continue;
+                }
+                let span = row.get_span(column_index);
+                let columns = span.map_or(1, |x| x.columns).min(column_count - column_index);
+                let rows = span.map_or(1, |x| x.rows).min(level_count + 1 - level);
+                for covered in covered_levels[column_index..column_index + columns].iter_mut() {
+                    *covered = rows - 1;
+                }
+
+                let percent_start = first.percent_range.start + self.get_columns_percent(0..column_index);
+                let percent_end = percent_start + self.get_columns_percent(column_index..column_index + columns);
+                let x = first.page_size_info.get_x_for_percent(first.page_number, percent_start);
+                let width = first.page_size_info.get_content_width() * (percent_end - percent_start) / 100.0;
+                let top = band_top(level);
+                let reaches_header = level + rows > level_count;
+                let bottom = if reaches_header {
+                    block_bottom
+                } else {
+                    band_top(level + rows)
+                };
+                let inner_x = if column_index == 0 { inner_left } else { x + cell_margin };
+                let inner_end = if column_index + columns == column_count {
+                    inner_right
+                } else {
+                    x + width - cell_margin
+                };
+                let inner_y = if level == 0 { top + top_shrink } else { top + cell_margin };
+                let inner_end_y = if reaches_header {
+                    inner_bottom
+                } else {
+                    bottom - cell_margin
+                };
+                let mut placement_info = first.clone();
+                placement_info.rec = Rectangle::new(x, top, width, bottom - top);
+                placement_info.restricted_area_option =
+                    Some(Rectangle::new(inner_x, inner_y, inner_end - inner_x, inner_end_y - inner_y));
+                placement_info.percent_range = percent_start..percent_end;
+
+                let value = row.data.get(column_index).unwrap_or(&CellValue::Empty);
+                let mut text_box = TextBox::new(
+                    value.to_string(),
+                    self.list_data.header_font.clone(),
+                    Some(self.get_row_align(column_index, true)),
+                    None,
+                    Some(background),
+                    if self.list_data.exclude_border_on_header {
+                        None
+                    } else {
+                        self.list_data.group
+                    },
+                );
+                text_box.on_draw(placement_info, pdf_draw, borders);
+                for covered_index in column_index + 1..column_index + columns {
+                    hidden_lines.push((covered_index, top, bottom - top));
+                }
+            }
+        }
+        hidden_lines
+    }
+
+    //footer and carry rows are sized by the footer font.
+    fn get_summary_row_pixels(&self) -> f64 {
+        0.0159708658854167 * self.list_data.footer_font.size * self.manager.get_page_pixel_dims().2 * 1.2
+    }
+
+    //a group header that goes on a page of its own.
+    fn starts_new_page(&mut self, row_index: usize) -> bool {
+        self.list_data.new_page_per_group
+            && row_index > 0
+            && matches!(self.list_data.get_slot(row_index), Some((RowSlot::GroupHeader, _)))
+    }
+
+    //height of the rows that the row at row_index wants on its page.
+    fn get_keep_with_next_pixels(&mut self, row_index: usize) -> f64 {
+        let mut keep_with_next = match self.list_data.get_slot(row_index) {
+            //a cell merged over rows keeps them on its page
+            Some((RowSlot::Data(_), row)) => row
+                .spans
+                .iter()
+                .map(|x| x.rows - 1)
+                .fold(row.keep_with_next, usize::max),
+            //a group header is never left alone at the bottom of a page
+            Some((RowSlot::GroupHeader, _)) => 1,
+            Some((RowSlot::GroupFooter, _)) => 0,
+            _ => return 0.0,
+        };
+        //footers never go on a page without a row of what they total
+        if let Some((RowSlot::GroupFooter, _)) | Some((RowSlot::Footer, _)) =
+            self.list_data.get_slot(row_index + 1)
+        {
+            keep_with_next = keep_with_next.max(1);
+        }
+        let mut kept_pixels = 0.0;
+        for kept_index in row_index + 1..row_index + 1 + keep_with_next {
+            if self.list_data.get_slot(kept_index).is_none() {
+                break;
+            }
+            kept_pixels += self.get_row_height_pixels(kept_index) + self.list_data.inner_border_size * 2.0;
+        }
+        kept_pixels
+    }
+
     fn get_position_border_info(
         &mut self,
         row_index: usize,
-        range: Range<usize>,
+        range: Range<f64>,
         is_start_row: bool,
         is_on_last_row: bool,
+        go_to_next_page: bool,
         page_index: &mut usize,
     ) -> BorderPositionData {
         // let mut list_placement_handle: CurrentPlacement = CurrentPlacement::default();
         let mut next = false;
-        let go_to_next_page = false;
         let (item_height_pixels, next_juan) = self.get_item_height_pixels(row_index);
         let mut border_position_data = BorderPositionData::default();
         let has_header = self.list_data.header.is_some();
         border_position_data.item_height_pixels = item_height_pixels;
+        //room left at the bottom of each page for a carried forward row
+        let carry_reserve_pixels = if is_on_last_row {
+            0.0
+        } else {
+            self.get_carry_reserve_pixels()
+        };
         while RowPositionType::Searching == border_position_data.row_type_and_size {
             border_position_data.list_placement_handle = self
                 .manager
@@ -810,14 +1828,16 @@ impl<'a> ListBox<'a> {
                     + self.list_data.inner_border_size)
                     + (next_juan
                         + self.list_data.outer_border_size
-                        + self.list_data.inner_border_size);
+                        + self.list_data.inner_border_size)
+                    + carry_reserve_pixels;
             let is_room_for_bottom_row = border_position_data
                 .list_placement_handle
                 .get_placement_info()
                 .draw_height_left_on_page
                 > (item_height_pixels
                     + self.list_data.outer_border_size
-                    + self.list_data.inner_border_size);
+                    + self.list_data.inner_border_size)
+                    + carry_reserve_pixels;
             let is_room_for_header_row = border_position_data
                 .list_placement_handle
                 .get_placement_info()
@@ -827,7 +1847,8 @@ impl<'a> ListBox<'a> {
                     + self.list_data.inner_border_size)
                     + (next_juan
                         + self.list_data.outer_border_size
-                        + self.list_data.inner_border_size);
+                        + self.list_data.inner_border_size)
+                    + carry_reserve_pixels;
 
             border_position_data.row_type_and_size = match (
                 is_on_last_row,
@@ -1012,21 +2033,58 @@ impl<'a> ListBox<'a> {
     pub fn set_item_column_alignments(&mut self, row_alignments: Vec<TextAlignment>) {
         self.list_data.column_text_alignments = Some(row_alignments);
     }
+    ///Rows stacked over the header, top first, like quarters over the months under them. They're
+    ///drawn with the header as one block on every page, and their cells take in the columns or
+    ///levels beside and under them with RowData::set_span. Needs a header.
+    pub fn set_header_levels(&mut self, levels: Vec<&'a RowData>) {
+        let level_count = levels.len() as f64;
+        let (_, page_height, _) = self.manager.get_page_pixel_dims();
+        let margins = self.manager.get_page_pixel_margins();
+        self.list_data.row_header_pixels = (self.list_data.header_line_pixels * (level_count + 1.0)
+            + self.list_data.inner_border_size * 2.0 * level_count)
+            .min(
+                page_height
+                    - margins.top_margin_pixels
+                    - margins.bottom_margin_pixels
+                    - self.list_data.outer_border_size * 4.0,
+            );
+        self.list_data.header_levels = levels;
+    }
     pub fn set_header_column_alignments(&mut self, column_alignments: Vec<TextAlignment>) {
         self.list_data.header_column_text_alignments = Some(column_alignments);
     }
     pub fn set_all_row_sizes(&mut self, cell_size: Vec<f64>) {
         self.list_data.row_cell_size = Some(cell_size);
     }
+    ///Works out each row's height in pixels when the row is reached, in place of set_all_row_sizes.
+    pub fn set_row_height<F: Fn(&RowData) -> f64 + 'a>(&mut self, row_height: F) {
+        self.list_data.row_height = Some(Box::new(row_height));
+    }
 
     pub fn set_row_types(&mut self, types: Vec<TypeOfItem>) {
         self.list_data.types_of_items = types;
     }
+    ///Wraps cell text to the width of its column, with rows growing to fit the tallest cell when
+    ///their heights aren't set.
+    pub fn set_wrap_cells(&mut self, wrap_cells: bool) {
+        self.list_data.wrap_cells = wrap_cells;
+    }
+    ///Rows sized by their wrapped cells that are taller than a page are split, the text cells
+    ///carrying on in a row of their own on the next page under the header. Rows with set heights
+    ///and rows with nothing left to carry on are cut off at a page.
+    pub fn set_split_tall_rows(&mut self, split_tall_rows: bool) {
+        self.list_data.split_tall_rows = split_tall_rows;
+    }
+    ///Column types and item alignments of a ListRow, so they can't drift from its rows.
+    pub fn set_columns_from<R: ListRow>(&mut self) {
+        self.list_data.types_of_items = R::column_types();
+        self.list_data.column_text_alignments = Some(R::column_alignments());
+    }
     fn place_level_line<T: DrawInfoReq>(
         &mut self,
         top_y_current_page: &mut f64,
-        percentage_start_x: usize,
-        percentage_width_x: usize,
+        percentage_start_x: f64,
+        percentage_width_x: f64,
         pdf_draw: &mut T,
         borders: &Option<RefCell<Vec<Border>>>,
     ) -> PlacementInfo {
@@ -1054,6 +2112,161 @@ impl<'a> ListBox<'a> {
     pub fn header_has_border(&mut self, does_it: bool) {
         self.list_data.exclude_border_on_header = !does_it;
     }
+    ///Adds a totals row after the data, one aggregate per column. It always shares a page with
+    ///the last data row.
+    pub fn set_footer(&mut self, aggregates: Vec<Aggregate>) {
+        self.list_data.footer_aggregates = Some(aggregates);
+    }
+    pub fn set_footer_style(&mut self, font: FontInfo, background: (f64, f64, f64)) {
+        self.list_data.footer_font = font;
+        self.list_data.footer_background = background;
+    }
+    ///Groups the rows by the value of a column, see set_group_by.
+    pub fn set_group_by_column(&mut self, column_index: usize) {
+        self.set_group_by(move |row: &RowData| {
+            row.data.get(column_index).map(|x| x.to_string()).unwrap_or_default()
+        });
+    }
+    ///Groups the rows by a key. A group header with the key is drawn wherever it changes between
+    ///rows, so the data should already be sorted. Single rows are left out of the groups.
+    pub fn set_group_by<F: Fn(&RowData) -> String + 'a>(&mut self, key: F) {
+        self.list_data.group_by = Some(Box::new(key));
+    }
+    ///Text color and alignment of the group header rows.
+    pub fn set_group_header_style(&mut self, font_color: (f64, f64, f64), alignment: TextAlignment) {
+        self.list_data.group_header_style = (font_color, alignment);
+    }
+    ///Adds a row of aggregates after each group, a Label can use {group} for the group's key.
+    pub fn set_group_footer(&mut self, aggregates: Vec<Aggregate>) {
+        self.list_data.group_footer_aggregates = Some(aggregates);
+    }
+    ///Starts the alternate row colors over after each group header.
+    pub fn set_restart_zebra_per_group(&mut self, restart: bool) {
+        self.list_data.restart_zebra_per_group = restart;
+    }
+    ///Puts every group after the first at the top of a new page.
+    pub fn set_new_page_per_group(&mut self, new_page: bool) {
+        self.list_data.new_page_per_group = new_page;
+    }
+    //the footer's values, left as numbers like the data so the column type formats them.
+    fn get_aggregate_row(aggregates: &[Aggregate], totals: &ColumnTotals, group: &str) -> RowData {
+        let cells = aggregates
+            .iter()
+            .enumerate()
+            .map(|(column_index, aggregate)| {
+                let sum = totals.sums.get(column_index).cloned().unwrap_or_default();
+                let numbers = totals.numbers.get(column_index).cloned().unwrap_or_default();
+                match aggregate {
+                    Aggregate::None => CellValue::Empty,
+                    Aggregate::Label(label) => CellValue::Text(label.replace("{group}", group)),
+                    Aggregate::Sum => CellValue::Decimal(sum),
+                    Aggregate::Count => {
+                        totals.non_empty.get(column_index).cloned().unwrap_or_default().into()
+                    }
+                    Aggregate::Average if numbers > 0 => CellValue::Decimal(sum / numbers as f64),
+                    Aggregate::Average => CellValue::Empty,
+                }
+            })
+            .collect();
+        RowData::new(cells, RowDataTypes::Normal)
+    }
+    //counts and labels are shown as they are, sums and averages use the column's type.
+    fn get_aggregate_type(
+        types_of_items: &[TypeOfItem],
+        aggregates: Option<&Vec<Aggregate>>,
+        column_index: usize,
+    ) -> TypeOfItem {
+        match aggregates.and_then(|x| x.get(column_index)) {
+            Some(Aggregate::Sum) | Some(Aggregate::Average) => types_of_items[column_index].clone(),
+            _ => TypeOfItem::String,
+        }
+    }
+    ///Where the table breaks across pages, adds a row with the running totals of columns at the
+    ///bottom of the page and repeats it at the top of the next, under the header.
+    pub fn set_carry_forward(&mut self, columns: Vec<usize>, carried_label: &str, brought_label: &str) {
+        self.list_data.carry_forward = Some(CarryForward {
+            columns,
+            carried_label: carried_label.to_string(),
+            brought_label: brought_label.to_string(),
+        });
+    }
+    fn add_to_carry_totals(carry_totals: &mut [f64], row: &RowData) {
+        if row.param != RowDataTypes::Normal {
+            return;
+        }
+        for (total, cell) in carry_totals.iter_mut().zip(row.data.iter()) {
+            if let Some(value) = cell.as_f64() {
+                *total += value;
+            }
+        }
+    }
+    //the label goes in the first column that isn't carrying a total.
+    fn get_carry_row(&self, carry_row: CarryRow, carry_totals: &[f64]) -> RowData {
+        let carry_forward = self.list_data.carry_forward.as_ref().unwrap();
+        let label = match carry_row {
+            CarryRow::CarriedForward => &carry_forward.carried_label,
+            CarryRow::BroughtForward => &carry_forward.brought_label,
+        };
+        let label_column = (0..carry_totals.len()).find(|x| !carry_forward.columns.contains(x));
+        let cells = (0..carry_totals.len())
+            .map(|column_index| {
+                if carry_forward.columns.contains(&column_index) {
+                    CellValue::Decimal(carry_totals[column_index])
+                } else if Some(column_index) == label_column {
+                    CellValue::Text(label.clone())
+                } else {
+                    CellValue::Empty
+                }
+            })
+            .collect();
+        RowData::new(cells, RowDataTypes::Normal)
+    }
+    fn get_carry_type(&self, column_index: usize) -> TypeOfItem {
+        match &self.list_data.carry_forward {
+            Some(carry_forward) if carry_forward.columns.contains(&column_index) => {
+                self.list_data.types_of_items[column_index].clone()
+            }
+            _ => TypeOfItem::String,
+        }
+    }
+    //carried forward closes the page, brought forward follows the header or starts the next page.
+    fn get_carry_row_border_info(&mut self, carry_row: CarryRow, range: Range<f64>) -> BorderPositionData {
+        let mut border_position_data = BorderPositionData {
+            item_height_pixels: self.get_summary_row_pixels(),
+            ..Default::default()
+        };
+        match carry_row {
+            CarryRow::CarriedForward => {
+                border_position_data.list_placement_handle =
+                    self.manager.get_placement_handle(range, false);
+                border_position_data.is_bottom_border = true;
+                border_position_data.row_type_and_size = RowPositionType::Bottom;
+            }
+            CarryRow::BroughtForward if self.list_data.header.is_some() => {
+                border_position_data.list_placement_handle =
+                    self.manager.get_placement_handle(range, false);
+                border_position_data.row_type_and_size = if self.list_data.exclude_border_on_header {
+                    border_position_data.is_top_border = true;
+                    RowPositionType::ItemWithTopBorder
+                } else {
+                    RowPositionType::Item
+                };
+            }
+            CarryRow::BroughtForward => {
+                border_position_data.list_placement_handle =
+                    self.manager.get_placement_handle(range, true);
+                border_position_data.is_top_border = true;
+                border_position_data.row_type_and_size = RowPositionType::Top;
+            }
+        }
+        border_position_data
+    }
+    ///After drawing, the column's area on the table's last page is kept under name in the manager.
+    pub fn name_column(&mut self, column_index: usize, name: &str) {
+        self.list_data
+            .column_names
+            .push((column_index, name.to_string()));
+    }
     fn get_row_color(&self, index_on_page: usize) -> (f64, f64, f64) {
         if let Some((color_a, color_b)) = self.list_data.alternate_row_colors {
             if index_on_page % 2 == 0 {
@@ -1086,38 +2299,33 @@ impl<'a> ListBox<'a> {
     }
     fn format_column(
         type_of_item: &TypeOfItem,
-        text: &String,
+        value: &CellValue,
         default_color: (f64, f64, f64),
         is_header_row: bool,
     ) -> (String, (f64, f64, f64)) {
         if is_header_row {
-            return (text.clone(), default_color);
+            return (value.to_string(), default_color);
         }
         let nan_color = (0.5, 0.3, 0.5);
-        match type_of_item {
-            TypeOfItem::String => (text.clone(), default_color),
-            TypeOfItem::Currency(precision) => {
-                if let Ok(currency) = text.parse::<f64>() {
-                    if currency < 0.0 {
-                        (format!("(${:.1$})", -currency, precision), (1.0, 0.0, 0.0))
-                    } else {
-                        (format!("${:.1$}", currency, precision), default_color)
-                    }
+        match (type_of_item, value.as_f64()) {
+            (TypeOfItem::String, _) => (value.to_string(), default_color),
+            (_, None) if value.is_empty() => (String::new(), default_color),
+            (TypeOfItem::Currency(precision), Some(currency)) => {
+                if currency < 0.0 {
+                    (format!("(${:.1$})", -currency, precision), (1.0, 0.0, 0.0))
                 } else {
-                    ("NAN".into(), nan_color) //(current_row[column_index].clone(), this_text_color)
+                    (format!("${:.1$}", currency, precision), default_color)
                 }
             }
-            TypeOfItem::Number(precision) => {
-                if let Ok(num) = text.parse::<f64>() {
-                    if num < 0.0 {
-                        (format!("{:.1$}", text, precision), (1.0, 0.0, 0.0))
-                    } else {
-                        (format!("{:.1$}", text, precision), default_color)
-                    }
+            (TypeOfItem::Number(precision), Some(num)) => {
+                if num < 0.0 {
+                    (format!("{:.1$}", num, precision), (1.0, 0.0, 0.0))
                 } else {
-                    ("NAN".into(), nan_color) // (current_row[column_index].clone(), this_text_color)
+                    (format!("{:.1$}", num, precision), default_color)
                 }
             }
+            //text that isn't a number in a number column
+            (_, None) => ("NAN".into(), nan_color), //(current_row[column_index].clone(), this_text_color)
         }
     }
     fn adjust_for_border(
@@ -1600,3 +2808,454 @@ impl Display for BorderPositionData {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::test_helpers::Pages;
+    use backfat_derive::ListRow;
+    use lopdf::content::Operation;
+    use lopdf::Object;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn manager() -> Manager {
+        Manager::new(8.5, 11.0, 72.0, 0.5, 0.5)
+    }
+
+    fn list_box<'a, R: Into<RowSource<'a>>>(rows: R, columns: usize, manager: &'a mut Manager) -> ListBox<'a> {
+        ListBox::new(
+            rows,
+            vec![100.0 / columns as f64; columns],
+            None,
+            manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        )
+    }
+
+    fn draw(list_box: &mut ListBox) -> Pages {
+        let mut pages = Pages::default();
+        let mut placement_handle = list_box.manager.get_placement_handle(0..100, false);
+        placement_handle.draw(list_box, &mut pages, &None);
+        pages
+    }
+
+    fn words(count: usize) -> String {
+        (0..count).map(|x| format!("word{}", x)).collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn rows_with_set_heights_are_not_split() {
+        let rows = vec![
+            RowData::new(vec!["first", &words(400)], RowDataTypes::Normal),
+            RowData::new(vec!["last", "short"], RowDataTypes::Normal),
+        ];
+        let mut manager = manager();
+        let mut list_box = list_box(&rows, 2, &mut manager);
+        list_box.set_wrap_cells(true);
+        list_box.set_split_tall_rows(true);
+        list_box.set_all_row_sizes(vec![2000.0, 20.0]);
+        let texts = draw(&mut list_box).all_texts();
+        assert_eq!(texts.iter().filter(|x| *x == "first").count(), 1);
+        assert_eq!(texts.iter().filter(|x| *x == "last").count(), 1);
+
+        let mut manager = self::manager();
+        let mut list_box = self::list_box(&rows, 2, &mut manager);
+        list_box.set_wrap_cells(true);
+        list_box.set_split_tall_rows(true);
+        list_box.set_row_height(|_| 2000.0);
+        let texts = draw(&mut list_box).all_texts();
+        assert_eq!(texts.iter().filter(|x| *x == "last").count(), 1);
+    }
+
+    #[test]
+    fn rows_that_cant_carry_on_are_cut_off() {
+        let rows = vec![
+            RowData::new(vec![CellValue::RichText(vec![TextRun::new(words(1500))]), "x".into()], RowDataTypes::Normal),
+            RowData::new(vec!["last", "short"], RowDataTypes::Normal),
+        ];
+        let mut manager = manager();
+        let mut list_box = list_box(&rows, 2, &mut manager);
+        list_box.set_wrap_cells(true);
+        list_box.set_split_tall_rows(true);
+        let pages = draw(&mut list_box);
+        assert_eq!(pages.all_texts().iter().filter(|x| *x == "x").count(), 1);
+        assert!(pages.pages.len() <= 2);
+    }
+
+    #[test]
+    fn split_rows_keep_numbers_whole() {
+        //the tall row starts near the bottom of the page where its wrapped number doesn't fit
+        let rows = vec![
+            RowData::new(vec![CellValue::Decimal(1.0), "filler".into()], RowDataTypes::Normal),
+            RowData::new(vec![CellValue::Decimal(123456789012345678.0), words(1500).into()], RowDataTypes::Normal),
+        ];
+        let mut manager = manager();
+        let mut list_box = ListBox::new(
+            &rows,
+            vec![8.0, 92.0],
+            None,
+            &mut manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        );
+        list_box.set_row_types(vec![TypeOfItem::Currency(2), TypeOfItem::String]);
+        list_box.set_all_row_sizes(vec![690.0]);
+        list_box.set_wrap_cells(true);
+        list_box.set_split_tall_rows(true);
+        let pages = draw(&mut list_box);
+        let texts = pages.all_texts();
+        assert!(pages.pages.len() > 1);
+        assert!(!texts.iter().any(|x| x.contains("NAN")));
+        assert!(texts.iter().any(|x| x.contains("word1499")));
+    }
+
+    fn spanned_rows(count: usize, rows: usize) -> Vec<RowData> {
+        (0..count)
+            .map(|x| {
+                let mut row = RowData::new(vec![format!("L{}", x), format!("R{}", x)], RowDataTypes::Normal);
+                if x % rows == 0 {
+                    row.set_span(0, 1, rows);
+                }
+                row
+            })
+            .collect()
+    }
+
+    //every row shows its right cell once and only the first row of each merge its left one
+    fn assert_merged(pages: &Pages, count: usize, rows: usize) {
+        let texts = pages.all_texts();
+        for x in 0..count {
+            let left = format!("L{}", x);
+            let shown = texts.iter().filter(|y| **y == left).count();
+            assert_eq!(shown, if x % rows == 0 { 1 } else { 0 }, "{}", left);
+            let right = format!("R{}", x);
+            assert_eq!(texts.iter().filter(|y| **y == right).count(), 1, "{}", right);
+        }
+    }
+
+    #[test]
+    fn row_spans_stay_merged_across_pages() {
+        let rows = spanned_rows(120, 3);
+        let mut manager = manager();
+        let mut list_box = list_box(&rows, 2, &mut manager);
+        let pages = draw(&mut list_box);
+        assert!(pages.pages.len() > 1);
+        assert_merged(&pages, 120, 3);
+        //the rows of a merge are on one page
+        for page in pages.texts() {
+            let first_right = page.iter().find(|x| x.starts_with('R')).unwrap();
+            assert_eq!(first_right[1..].parse::<usize>().unwrap() % 3, 0, "{}", first_right);
+        }
+
+        let header = RowData::new(vec!["Left", "Right"], RowDataTypes::Normal);
+        let mut manager = self::manager();
+        let mut list_box = ListBox::new(
+            &rows,
+            vec![50.0, 50.0],
+            Some(&header),
+            &mut manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        );
+        let pages = draw(&mut list_box);
+        assert!(pages.pages.len() > 1);
+        assert_merged(&pages, 120, 3);
+        for page in pages.texts() {
+            assert_eq!(page.iter().filter(|x| *x == "Left").count(), 1);
+        }
+    }
+
+    #[test]
+    fn row_spans_taller_than_a_page_carry_on_blank() {
+        let rows = spanned_rows(150, 75);
+        let mut manager = manager();
+        let mut list_box = list_box(&rows, 2, &mut manager);
+        let pages = draw(&mut list_box);
+        assert!(pages.pages.len() > 2);
+        assert_merged(&pages, 150, 75);
+    }
+
+    fn amounts(count: usize) -> Vec<RowData> {
+        (0..count)
+            .map(|x| RowData::new(vec![CellValue::Text(format!("G{}", x / 10)), CellValue::Integer(x as i64)], RowDataTypes::Normal))
+            .collect()
+    }
+
+    #[test]
+    fn footer_aggregates_every_row() {
+        let rows = amounts(30);
+        for (aggregate, expected) in [(Aggregate::Sum, "435.0"), (Aggregate::Average, "14.5"), (Aggregate::Count, "30")].iter() {
+            let mut manager = manager();
+            let mut list_box = list_box(&rows, 2, &mut manager);
+            list_box.set_row_types(vec![TypeOfItem::String, TypeOfItem::Number(1)]);
+            list_box.set_footer(vec![Aggregate::Label("Total".into()), aggregate.clone()]);
+            let texts = draw(&mut list_box).all_texts();
+            assert_eq!(texts[texts.len() - 2..], ["Total".to_string(), expected.to_string()]);
+        }
+    }
+
+    #[test]
+    fn carried_forward_totals_match_the_rows_before_the_break() {
+        let rows = amounts(150);
+        let header = RowData::new(vec!["Group", "Amount"], RowDataTypes::Normal);
+        let mut manager = manager();
+        let mut list_box = ListBox::new(
+            &rows,
+            vec![50.0, 50.0],
+            Some(&header),
+            &mut manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        );
+        list_box.set_row_types(vec![TypeOfItem::String, TypeOfItem::Number(0)]);
+        list_box.set_carry_forward(vec![1], "Carried forward", "Brought forward");
+        let pages = draw(&mut list_box).texts();
+        assert!(pages.len() > 2);
+        for (index, page) in pages.iter().enumerate() {
+            let values: Vec<i64> = page
+                .iter()
+                .zip(page.iter().skip(1))
+                .filter(|(label, _)| label.starts_with('G'))
+                .filter_map(|(_, value)| value.parse().ok())
+                .collect();
+            let sum_before = |last: i64| (0..=last).sum::<i64>().to_string();
+            if index + 1 < pages.len() {
+                let carried = page.iter().position(|x| x == "Carried forward").unwrap();
+                assert_eq!(page[carried + 1], sum_before(*values.last().unwrap()));
+            }
+            if index > 0 {
+                let brought = page.iter().position(|x| x == "Brought forward").unwrap();
+                assert_eq!(page[brought + 1], sum_before(values[0] - 1));
+                assert!(brought > page.iter().position(|x| x == "Amount").unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn groups_get_headers_and_subtotals() {
+        let rows = amounts(30);
+        let mut manager = manager();
+        let mut list_box = list_box(&rows, 2, &mut manager);
+        list_box.set_row_types(vec![TypeOfItem::String, TypeOfItem::Number(0)]);
+        list_box.set_group_by_column(0);
+        list_box.set_group_footer(vec![Aggregate::Label("Subtotal {group}".into()), Aggregate::Sum]);
+        let texts = draw(&mut list_box).all_texts();
+        for (group, subtotal) in [("G0", "45"), ("G1", "145"), ("G2", "245")].iter() {
+            let header = texts.iter().position(|x| x == group).unwrap();
+            let footer = texts.iter().position(|x| *x == format!("Subtotal {}", group)).unwrap();
+            assert!(header < footer);
+            assert_eq!(texts[footer + 1], *subtotal);
+        }
+        assert_eq!(texts[texts.len() - 2..], ["Subtotal G2".to_string(), "245".to_string()]);
+    }
+
+    //keeps no operations, only how far the source has been read ahead of what is drawn
+    struct Streamed {
+        page_cnt: usize,
+        read: Rc<Cell<usize>>,
+        last_drawn: usize,
+        most_ahead: usize,
+    }
+    impl DrawInfoReq for Streamed {
+        fn increment_page_buffer(&mut self, page_number: usize) {
+            self.page_cnt = self.page_cnt.max(page_number + 1);
+        }
+        fn page_array_size(&self) -> usize {
+            self.page_cnt
+        }
+        fn insert_into_page(&mut self, _page_num: usize, operation: Operation) {
+            if let Some(Object::String(text, _)) = operation.operands.first() {
+                if let Some(drawn) = text.strip_prefix(b"R").and_then(|x| String::from_utf8_lossy(x).parse::<usize>().ok()) {
+                    self.last_drawn = drawn;
+                    self.most_ahead = self.most_ahead.max(self.read.get() - drawn);
+                }
+            }
+        }
+        fn page_operation_len(&self, _page_num: usize) -> usize {
+            0
+        }
+        fn truncate_page(&mut self, _page_num: usize, _len: usize) {}
+        fn truncate_pages(&mut self, _page_cnt: usize) {}
+    }
+
+    #[test]
+    fn streamed_rows_are_read_as_they_are_drawn() {
+        let read = Rc::new(Cell::new(0));
+        let counter = read.clone();
+        let rows = (0..200_000).map(move |x| {
+            counter.set(x + 1);
+            RowData::new(vec![format!("L{}", x), format!("R{}", x)], RowDataTypes::Normal)
+        });
+        let mut manager = manager();
+        let mut list_box = list_box(RowSource::stream(rows), 2, &mut manager);
+        let mut streamed = Streamed {
+            page_cnt: 0,
+            read: read.clone(),
+            last_drawn: 0,
+            most_ahead: 0,
+        };
+        let mut placement_handle = list_box.manager.get_placement_handle(0..100, false);
+        placement_handle.draw(&mut list_box, &mut streamed, &None);
+        assert_eq!(read.get(), 200_000);
+        assert_eq!(streamed.last_drawn, 199_999);
+        assert!(streamed.page_cnt > 1000);
+        assert!(streamed.most_ahead <= 3, "read {} rows ahead", streamed.most_ahead);
+    }
+
+    #[test]
+    fn cells_are_formatted_by_column_type() {
+        let black = (0.0, 0.0, 0.0);
+        let format = |type_of_item: TypeOfItem, value: CellValue| ListBox::format_column(&type_of_item, &value, black, false);
+        assert_eq!(format(TypeOfItem::Currency(2), CellValue::Decimal(12.5)), ("$12.50".to_string(), black));
+        assert_eq!(format(TypeOfItem::Currency(2), CellValue::Decimal(-3.5)), ("($3.50)".to_string(), (1.0, 0.0, 0.0)));
+        assert_eq!(format(TypeOfItem::Currency(0), CellValue::Text("7".into())).0, "$7");
+        assert_eq!(format(TypeOfItem::Number(1), CellValue::Decimal(2.26)).0, "2.3");
+        assert_eq!(format(TypeOfItem::Number(0), CellValue::Integer(-4)), ("-4".to_string(), (1.0, 0.0, 0.0)));
+        assert_eq!(format(TypeOfItem::Number(0), CellValue::Empty).0, "");
+        assert_eq!(format(TypeOfItem::Number(0), CellValue::Text("abc".into())).0, "NAN");
+        assert_eq!(format(TypeOfItem::String, CellValue::Integer(5)).0, "5");
+        //headers show what they're given whatever the column holds
+        assert_eq!(ListBox::format_column(&TypeOfItem::Currency(2), &CellValue::Text("Amount".into()), black, true).0, "Amount");
+    }
+
+    #[derive(ListRow)]
+    struct Line {
+        #[list_row(width = 20.0, header = "Item No")]
+        item: i64,
+        name: String,
+        #[list_row(width = 30.0, currency = 2, align = "RightCenter")]
+        amount: f64,
+        #[list_row(skip)]
+        _note: String,
+    }
+
+    #[test]
+    fn derived_rows_know_their_columns() {
+        let line = Line {
+            item: 3,
+            name: "Bolt".into(),
+            amount: 1.5,
+            _note: String::new(),
+        };
+        let row = line.to_row();
+        assert_eq!(row.data.len(), 3);
+        assert_eq!(row.data[0].to_string(), "3");
+        assert_eq!(row.data[1].to_string(), "Bolt");
+        assert_eq!(row.data[2].as_f64(), Some(1.5));
+        let header: Vec<String> = Line::header().data.iter().map(|x| x.to_string()).collect();
+        assert_eq!(header, vec!["Item No", "Name", "Amount"]);
+        assert_eq!(Line::column_widths(100.0), vec![20.0, 50.0, 30.0]);
+        assert_eq!(Line::column_widths(40.0), vec![8.0, 20.0, 12.0]);
+        assert!(matches!(Line::column_types()[..], [TypeOfItem::String, TypeOfItem::String, TypeOfItem::Currency(2)]));
+        assert!(matches!(Line::column_alignments()[2], TextAlignment::RightCenter));
+
+        let rows = vec![line.to_row()];
+        let header = Line::header();
+        let mut manager = manager();
+        let mut list_box = ListBox::new(
+            &rows,
+            Line::column_widths(100.0),
+            Some(&header),
+            &mut manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        );
+        list_box.set_columns_from::<Line>();
+        let texts = draw(&mut list_box).all_texts();
+        assert!(texts.contains(&"$1.50".to_string()));
+    }
+
+    #[test]
+    fn wrapped_cells_fit_their_column() {
+        let rows = vec![
+            RowData::new(vec![words(40), "x".into()], RowDataTypes::Normal),
+            RowData::new(vec!["after", "y"], RowDataTypes::Normal),
+        ];
+        let mut manager = manager();
+        let mut list_box = ListBox::new(
+            &rows,
+            vec![20.0, 80.0],
+            None,
+            &mut manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        );
+        list_box.set_wrap_cells(true);
+        let texts = draw(&mut list_box).all_texts();
+        let lines: Vec<&String> = texts.iter().filter(|x| x.starts_with("word")).collect();
+        assert!(lines.len() > 5);
+        let joined: Vec<String> = lines.iter().map(|x| x.to_string()).collect();
+        assert_eq!(joined.join(" "), words(40));
+    }
+
+    #[test]
+    fn split_rows_carry_on_under_the_header() {
+        let rows = vec![
+            RowData::new(vec!["first".into(), words(1500)], RowDataTypes::Normal),
+            RowData::new(vec!["last", "short"], RowDataTypes::Normal),
+        ];
+        let header = RowData::new(vec!["Name", "Text"], RowDataTypes::Normal);
+        let mut manager = manager();
+        let mut list_box = ListBox::new(
+            &rows,
+            vec![20.0, 80.0],
+            Some(&header),
+            &mut manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        );
+        list_box.set_wrap_cells(true);
+        list_box.set_split_tall_rows(true);
+        let pages = draw(&mut list_box).texts();
+        assert!(pages.len() > 1);
+        for page in &pages {
+            assert_eq!(page.iter().filter(|x| *x == "Text").count(), 1);
+        }
+        let text: Vec<String> = pages.iter().flatten().filter(|x| x.starts_with("word")).cloned().collect();
+        assert_eq!(text.join(" "), words(1500));
+        assert_eq!(pages.iter().flatten().filter(|x| *x == "first").count(), 1);
+        assert_eq!(pages.iter().flatten().filter(|x| *x == "last").count(), 1);
+    }
+
+    #[test]
+    fn header_levels_repeat_with_the_header_on_every_page() {
+        let rows = amounts(150);
+        let header = RowData::new(vec!["Group", "Amount"], RowDataTypes::Normal);
+        let mut level = RowData::new(vec!["Sales", ""], RowDataTypes::Normal);
+        level.set_span(0, 2, 1);
+        let mut manager = manager();
+        let mut list_box = ListBox::new(
+            &rows,
+            vec![50.0, 50.0],
+            Some(&header),
+            &mut manager,
+            FontInfo::new(8.0, Font::Helvetica),
+            FontInfo::new(8.0, Font::Helvetica),
+            ListBoxBorder::All(1.0, 1.0),
+            None,
+        );
+        list_box.set_header_levels(vec![&level]);
+        let pages = draw(&mut list_box).texts();
+        assert!(pages.len() > 2);
+        for page in &pages {
+            assert_eq!(page.iter().filter(|x| *x == "Sales").count(), 1);
+            assert_eq!(page.iter().filter(|x| *x == "Group").count(), 1);
+        }
+    }
+}
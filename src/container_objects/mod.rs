@@ -1,7 +1,10 @@
 use crate::container::container_trait::DrawInfoReq;
 use lopdf::content::Operation;
 
+pub mod grid;
 pub mod lines;
 pub mod list_box;
+pub mod panel;
+pub mod section;
 pub mod text_box;
 
@@ -0,0 +1,195 @@
+use crate::container::container_trait::{Container, ContainerTrait, DrawInfoReq};
+use crate::container::layout_context::LayoutContext;
+use crate::container::manager::{CurrentPlacement, LocationInfo};
+use crate::container::page_master::PageMaster;
+use crate::container::page_size_info::PageSizeInfo;
+use crate::container::placement_info::PlacementInfo;
+use crate::container::rectangle::{Border, Rectangle};
+use crate::container_objects::lines::{draw_filled_rectangle, draw_rectangle};
+use crate::container_objects::text_box::BorderStyle;
+use crate::container::span::Span;
+use std::cell::RefCell;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+///A box with its own flow. Children are stacked with percent ranges relative to the
+///inside of the panel and the panel grows to fit them. A panel is never split across pages.
+pub struct Panel {
+    children: Vec<(LocationInfo, Box<dyn Container>)>,
+    border_style: BorderStyle,
+    border_color: (f64, f64, f64),
+    background: Option<(f64, f64, f64)>,
+    padding: f64,
+    group: Option<usize>,
+}
+
+impl Panel {
+    pub fn new(
+        border_style: Option<BorderStyle>,
+        background: Option<(f64, f64, f64)>,
+        padding: f64,
+        group: Option<usize>,
+    ) -> Self {
+        Self {
+            children: Vec::new(),
+            border_style: border_style.unwrap_or(BorderStyle::None),
+            border_color: (0.0, 0.0, 0.0),
+            background,
+            padding,
+            group,
+        }
+    }
+    ///Adds a child below the ones already added in the same range. The range is a percent of the panel.
+    pub fn add(&mut self, location: LocationInfo, container: Box<dyn Container>) {
+        self.children.push((location, container));
+    }
+    pub fn set_border_color(&mut self, border_color: (f64, f64, f64)) {
+        self.border_color = border_color;
+    }
+
+    //places the children inside a panel of this width, placements are relative to the panel interior.
+    fn layout_children(&self, inner_width: f64, page_size_info: &PageSizeInfo) -> Vec<PlacementInfo> {
+        let page_master = Arc::new(Mutex::new(PageMaster::new(
+            inner_width,
+            f64::INFINITY,
+            page_size_info.dpi,
+            0.0,
+            0.0,
+        )));
+        let mut placements = Vec::with_capacity(self.children.len());
+        for (location, container) in &self.children {
+            let mut placement_handle =
+                CurrentPlacement::new(&page_master, Some(location.range), false);
+            match location.height {
+                Some(height) => placement_handle.set_pixel_height(height),
+                None => placement_handle.set_height_from_content(container, None, None),
+            }
+            let placement = placement_handle.get_placement_info();
+            page_master.lock().unwrap().update_placement(&placement);
+            placements.push(placement);
+        }
+        placements
+    }
+
+    //a child's range in percent of the page, for children that look up their x on a later page.
+    fn get_page_percent_range(panel_placement: &PlacementInfo, x: f64, width: f64) -> Range<f64> {
+        let page_size_info = &panel_placement.page_size_info;
+        let left = panel_placement.rec.x - page_size_info.get_left_margin(panel_placement.page_number);
+        page_size_info.get_percent_range(Span::pixels(left + x, width))
+    }
+
+    fn get_children_height(placements: &[PlacementInfo]) -> f64 {
+        placements
+            .iter()
+            .map(|x| x.rec.y + x.rec.height)
+            .fold(0.0, f64::max)
+    }
+}
+
+impl ContainerTrait for Panel {
+    fn on_draw<T: DrawInfoReq>(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_to: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        let inner_width = placement_info.rec.width - self.padding * 2.0;
+        let placements = self.layout_children(inner_width, &placement_info.page_size_info);
+
+        let mut panel_placement = placement_info.clone();
+        panel_placement.rec.height = placement_info
+            .rec
+            .height
+            .max(Self::get_children_height(&placements) + self.padding * 2.0);
+
+        if let Some(background) = self.background {
+            draw_filled_rectangle(draw_to, &panel_placement, background);
+        }
+
+        for ((_, container), child) in self.children.iter_mut().zip(placements) {
+            let child_placement = PlacementInfo {
+                rec: Rectangle::new(
+                    panel_placement.rec.x + self.padding + child.rec.x,
+                    panel_placement.rec.y + self.padding + child.rec.y,
+                    child.rec.width,
+                    child.rec.height,
+                ),
+                is_new_page: false,
+                draw_height_left_on_page: panel_placement.draw_height_left_on_page
+                    - self.padding
+                    - child.rec.y,
+                page_number: panel_placement.page_number,
+                page_size_info: panel_placement.page_size_info.clone(),
+                percent_range: Self::get_page_percent_range(
+                    &panel_placement,
+                    self.padding + child.rec.x,
+                    child.rec.width,
+                ),
+                restricted_area_option: None,
+                page_layouts: panel_placement.page_layouts.clone(),
+            };
+            container.draw(child_placement, draw_to, borders);
+        }
+
+        match self.border_style {
+            BorderStyle::Single(size) => match borders {
+                None => {
+                    draw_rectangle(draw_to, &panel_placement, size, self.border_color);
+                }
+                Some(border) => {
+                    border.borrow_mut().push(Border::new(
+                        panel_placement.clone(),
+                        self.border_color,
+                        size,
+                    ));
+                }
+            },
+            BorderStyle::None => {}
+        };
+
+        Some(panel_placement)
+    }
+
+    fn get_group(&self) -> Option<usize> {
+        self.group
+    }
+
+    fn get_content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64> {
+        let placements = self.layout_children(width_pixels - self.padding * 2.0, page_size_info);
+        Some(Self::get_children_height(&placements) + self.padding * 2.0)
+    }
+
+    fn on_layout(&mut self, layout: &LayoutContext) {
+        for (_, container) in self.children.iter_mut() {
+            container.layout(layout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::span::Span;
+    use crate::container::test_helpers::{Block, Drawn, Pages};
+
+    #[test]
+    fn child_percent_range_is_of_the_page() {
+        let page_size_info = PageSizeInfo::new(612.0, 792.0, 72.0, 36.0, 36.0).with_mirrored_margins(72.0, 36.0);
+        let percent_range = page_size_info.get_percent_range(Span::Percent(50.0, 100.0));
+        let placement_info = PlacementInfo {
+            rec: Rectangle::new(page_size_info.get_x_for_percent(0, 50.0), 100.0, 252.0, 100.0),
+            draw_height_left_on_page: 600.0,
+            page_size_info: page_size_info.clone(),
+            percent_range,
+            ..Default::default()
+        };
+        let drawn = Drawn::default();
+        let mut panel = Panel::new(None, None, 10.0, None);
+        panel.add(LocationInfo::new(50..100, Some(20.0)), Box::new(Block::new(20.0, &drawn)));
+        panel.on_draw(placement_info, &mut Pages::default(), &None);
+        let child = &drawn.borrow()[0];
+        let x = page_size_info.get_x_for_percent(0, child.percent_range.start);
+        assert!((x - child.rec.x).abs() < 1e-9);
+        assert!((child.rec.x - (72.0 + 252.0 + 10.0 + 116.0)).abs() < 1e-9);
+    }
+}
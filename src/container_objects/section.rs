@@ -0,0 +1,305 @@
+use crate::container::container_trait::{Container, ContainerTrait, DrawInfoReq};
+use crate::container::layout_context::LayoutContext;
+use crate::container::page_size_info::PageSizeInfo;
+use crate::container::placement_info::PlacementInfo;
+use crate::container::rectangle::{Border, Rectangle};
+use std::cell::RefCell;
+
+//where an item ended up and how tall it is there, column tops are per page.
+#[derive(Clone, Copy)]
+struct SectionSlot {
+    page_offset: usize,
+    column: usize,
+    top: f64,
+    height: f64,
+}
+
+///Newspaper style columns. Items flow down the first column, then the next, and after the
+///last column on to the next page. Optionally the columns on the last page are balanced.
+pub struct Section {
+    column_count: usize,
+    gutter_widths: Vec<f64>,
+    balance_columns: bool,
+    items: Vec<(Option<f64>, Box<dyn Container>)>,
+    group: Option<usize>,
+}
+
+impl Section {
+    pub fn new(column_count: usize, gutter_pixels: f64, group: Option<usize>) -> Self {
+        let column_count = column_count.max(1);
+        Self {
+            column_count,
+            gutter_widths: vec![gutter_pixels; column_count - 1],
+            balance_columns: false,
+            items: Vec::new(),
+            group,
+        }
+    }
+    ///Width of each gutter from the left, missing ones are 0.
+    pub fn set_gutter_widths(&mut self, gutter_widths: Vec<f64>) {
+        self.gutter_widths = gutter_widths;
+        self.gutter_widths.resize(self.column_count - 1, 0.0);
+    }
+    ///Evens out the column heights on the last page of the section.
+    pub fn set_balance_columns(&mut self, balance_columns: bool) {
+        self.balance_columns = balance_columns;
+    }
+    ///Adds the next item, a None height uses the container's content height.
+    pub fn add(&mut self, height: Option<f64>, container: Box<dyn Container>) {
+        self.items.push((height, container));
+    }
+
+    fn get_column_width(&self, width_pixels: f64) -> f64 {
+        let gutters: f64 = self.gutter_widths.iter().sum();
+        (width_pixels - gutters) / self.column_count as f64
+    }
+    fn get_column_x(&self, column: usize, column_width: f64) -> f64 {
+        let gutters: f64 = self.gutter_widths[..column].iter().sum();
+        column as f64 * column_width + gutters
+    }
+    fn get_item_height(&self, index: usize, column_width: f64, page_size_info: &PageSizeInfo) -> f64 {
+        let (height, container) = &self.items[index];
+        height
+            .or_else(|| container.content_height(column_width, page_size_info))
+            .unwrap_or(0.0)
+    }
+    fn get_item_heights(&self, column_width: f64, page_size_info: &PageSizeInfo) -> Vec<f64> {
+        (0..self.items.len())
+            .map(|index| self.get_item_height(index, column_width, page_size_info))
+            .collect()
+    }
+
+    //fills columns top down with each column holding at most column_height, None if it runs out of columns.
+    fn fill_columns(&self, heights: &[f64], column_height: f64) -> Option<Vec<(usize, f64)>> {
+        let mut column = 0;
+        let mut top = 0.0;
+        let mut slots = Vec::with_capacity(heights.len());
+        for height in heights {
+            if top + height > column_height && top > 0.0 {
+                column += 1;
+                top = 0.0;
+            }
+            if column >= self.column_count {
+                return None;
+            }
+            slots.push((column, top));
+            top += height;
+        }
+        Some(slots)
+    }
+
+    //smallest column height that still fits everything in the columns of one page.
+    fn get_balanced_height(&self, heights: &[f64], available: f64) -> f64 {
+        let tallest = heights.iter().cloned().fold(0.0, f64::max);
+        let mut low = tallest;
+        let mut high = available.max(tallest);
+        if self.fill_columns(heights, low).is_some() {
+            return low;
+        }
+        for _ in 0..40 {
+            let middle = (low + high) / 2.0;
+            if self.fill_columns(heights, middle).is_some() {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        high
+    }
+
+    //items flow down the columns of each page, later pages are measured with their own width.
+    fn get_slots(&self, placement_info: &PlacementInfo) -> Vec<SectionSlot> {
+        let mut page = placement_info.clone();
+        let mut page_offset = 0;
+        let mut column_width = self.get_column_width(page.rec.width);
+        let mut column = 0;
+        let mut top = page.rec.y;
+        let mut slots = Vec::with_capacity(self.items.len());
+        for index in 0..self.items.len() {
+            let mut height = self.get_item_height(index, column_width, &page.page_size_info);
+            let page_bottom = page.page_size_info.page_height_pixels - page.page_size_info.bottom_margin;
+            if top + height > page_bottom && top > page.rec.y {
+                column += 1;
+                top = page.rec.y;
+                if column == self.column_count {
+                    column = 0;
+                    page_offset += 1;
+                    page = placement_info.get_page_placement(placement_info.page_number + page_offset);
+                    column_width = self.get_column_width(page.rec.width);
+                    height = self.get_item_height(index, column_width, &page.page_size_info);
+                    top = page.rec.y;
+                }
+            }
+            slots.push(SectionSlot {
+                page_offset,
+                column,
+                top,
+                height,
+            });
+            top += height;
+        }
+
+        if self.balance_columns && !slots.is_empty() {
+            let first_on_last_page = slots
+                .iter()
+                .position(|x| x.page_offset == page_offset)
+                .unwrap_or(0);
+            let page_bottom = page.page_size_info.page_height_pixels - page.page_size_info.bottom_margin;
+            let last_heights: Vec<f64> = slots[first_on_last_page..].iter().map(|x| x.height).collect();
+            let column_height = self.get_balanced_height(&last_heights, page_bottom - page.rec.y);
+            if let Some(balanced) = self.fill_columns(&last_heights, column_height) {
+                for (slot, (column, top)) in slots[first_on_last_page..].iter_mut().zip(balanced) {
+                    slot.column = column;
+                    slot.top = page.rec.y + top;
+                }
+            }
+        }
+        slots
+    }
+}
+
+impl ContainerTrait for Section {
+    fn on_draw<T: DrawInfoReq>(
+        &mut self,
+        placement_info: PlacementInfo,
+        draw_to: &mut T,
+        borders: &Option<RefCell<Vec<Border>>>,
+    ) -> Option<PlacementInfo> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let slots = self.get_slots(&placement_info);
+
+        let last_page_offset = slots[slots.len() - 1].page_offset;
+        let mut page = placement_info.clone();
+        let mut column_width = self.get_column_width(page.rec.width);
+        let mut bottom = page.rec.y;
+        for (index, slot) in slots.iter().enumerate() {
+            let page_number = placement_info.page_number + slot.page_offset;
+            if page_number != page.page_number {
+                page = placement_info.get_page_placement(page_number);
+                column_width = self.get_column_width(page.rec.width);
+                bottom = page.rec.y;
+            }
+            if page_number >= draw_to.page_array_size() {
+                draw_to.increment_page_buffer(page_number);
+            }
+            let page_bottom = page.page_size_info.page_height_pixels - page.page_size_info.bottom_margin;
+            let item_placement = PlacementInfo {
+                rec: Rectangle::new(
+                    page.rec.x + self.get_column_x(slot.column, column_width),
+                    slot.top,
+                    column_width,
+                    slot.height,
+                ),
+                is_new_page: slot.page_offset > 0 && slot.top == page.rec.y,
+                draw_height_left_on_page: page_bottom - slot.top,
+                page_number,
+                page_size_info: page.page_size_info.clone(),
+                percent_range: placement_info.percent_range.clone(),
+                restricted_area_option: None,
+                page_layouts: placement_info.page_layouts.clone(),
+            };
+            self.items[index].1.draw(item_placement, draw_to, borders);
+            bottom = bottom.max(slot.top + slot.height);
+        }
+
+        let mut last_placement = page.clone();
+        last_placement.rec.height = bottom - page.rec.y;
+        last_placement.is_new_page = last_page_offset > 0;
+        last_placement.draw_height_left_on_page =
+            page.page_size_info.page_height_pixels - page.page_size_info.bottom_margin - page.rec.y;
+        Some(last_placement)
+    }
+
+    fn get_group(&self) -> Option<usize> {
+        self.group
+    }
+
+    //a section that needs more than the columns of one page starts wherever its first item fits
+    //and flows on from there, so only that item has to fit where it's placed. Otherwise it's the
+    //tallest column, filled like on_draw fills them.
+    fn get_content_height(&self, width_pixels: f64, page_size_info: &PageSizeInfo) -> Option<f64> {
+        let heights = self.get_item_heights(self.get_column_width(width_pixels), page_size_info);
+        let page_height = page_size_info.page_height_pixels
+            - page_size_info.top_margin
+            - page_size_info.bottom_margin;
+        let column_height = if self.balance_columns {
+            self.get_balanced_height(&heights, page_height)
+        } else {
+            page_height
+        };
+        match self.fill_columns(&heights, column_height) {
+            Some(slots) => Some(
+                slots
+                    .iter()
+                    .zip(heights.iter())
+                    .map(|((_, top), height)| top + height)
+                    .fold(0.0, f64::max),
+            ),
+            None => heights.first().copied(),
+        }
+    }
+
+    fn on_layout(&mut self, layout: &LayoutContext) {
+        for (_, container) in self.items.iter_mut() {
+            container.layout(layout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::test_helpers::{placement, Block, Drawn, Pages};
+
+    fn section(items: usize, height: f64) -> (Section, Drawn) {
+        let drawn = Drawn::default();
+        let mut section = Section::new(2, 12.0, None);
+        for _ in 0..items {
+            section.add(None, Box::new(Block::new(height, &drawn)));
+        }
+        (section, drawn)
+    }
+
+    #[test]
+    fn later_pages_use_their_own_layout() {
+        let (mut section, drawn) = section(6, 50.0);
+        let last = section.on_draw(placement(), &mut Pages::default(), &None).unwrap();
+        let drawn = drawn.borrow();
+        assert_eq!(drawn[3].page_number, 0);
+        assert_eq!(drawn[3].rec.x, 312.0);
+        assert_eq!(drawn[4].page_number, 1);
+        assert_eq!(drawn[4].rec.y, 144.0);
+        assert_eq!(drawn[4].rec.x, 100.0);
+        assert_eq!(drawn[4].rec.width, 225.0);
+        assert_eq!(last.page_number, 1);
+        assert_eq!(last.rec.y, 144.0);
+        assert_eq!(last.rec.height, 100.0);
+    }
+
+    #[test]
+    fn last_page_height_starts_at_its_top() {
+        let (mut section, _) = section(6, 50.0);
+        section.set_balance_columns(true);
+        let last = section.on_draw(placement(), &mut Pages::default(), &None).unwrap();
+        assert_eq!(last.rec.y, 144.0);
+        assert_eq!(last.rec.height, 50.0);
+    }
+
+    #[test]
+    fn content_height_past_a_page_is_the_first_item() {
+        let page = PageSizeInfo::new(612.0, 792.0, 72.0, 72.0, 72.0);
+        let (long, _) = section(40, 50.0);
+        assert_eq!(long.get_content_height(612.0, &page), Some(50.0));
+    }
+
+    #[test]
+    fn content_height_is_balanced_only_when_asked() {
+        let page = PageSizeInfo::new(612.0, 792.0, 72.0, 72.0, 72.0);
+        let (mut short, _) = section(4, 50.0);
+        assert_eq!(short.get_content_height(612.0, &page), Some(200.0));
+        short.set_balance_columns(true);
+        assert!((short.get_content_height(612.0, &page).unwrap() - 100.0).abs() < 1e-6);
+    }
+}
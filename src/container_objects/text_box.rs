@@ -2,6 +2,7 @@ use crate::container::container_trait::{ContainerTrait, DrawInfoReq};
 use lopdf::content::Operation;
 use lopdf::Object;
 //use crate::container_objects::lines::*;
+use crate::container::layout_context::LayoutContext;
 use crate::container::page_size_info::PageSizeInfo;
 use crate::container::placement_info::PlacementInfo;
 use crate::container::rectangle::{Border, Rectangle};
@@ -35,6 +36,54 @@ pub enum TextAlignment {
     CenterCenter,
     CenterBottom,
 }
+impl TextAlignment {
+    ///The same alignment with left and right swapped, center stays put.
+    pub fn mirror(&self) -> Self {
+        match self {
+            TextAlignment::LeftTop => TextAlignment::RightTop,
+            TextAlignment::LeftCenter => TextAlignment::RightCenter,
+            TextAlignment::LeftBottom => TextAlignment::RightBottom,
+            TextAlignment::LeftJustifyTop(x) => TextAlignment::RightJustifyTop(*x),
+            TextAlignment::LeftJustifyCenter(x) => TextAlignment::RightJustifyCenter(*x),
+            TextAlignment::LeftJustifyBottom(x) => TextAlignment::RightJustifyBottom(*x),
+            TextAlignment::RightTop => TextAlignment::LeftTop,
+            TextAlignment::RightCenter => TextAlignment::LeftCenter,
+            TextAlignment::RightBottom => TextAlignment::LeftBottom,
+            TextAlignment::RightJustifyTop(x) => TextAlignment::LeftJustifyTop(*x),
+            TextAlignment::RightJustifyCenter(x) => TextAlignment::LeftJustifyCenter(*x),
+            TextAlignment::RightJustifyBottom(x) => TextAlignment::LeftJustifyBottom(*x),
+            TextAlignment::CenterTop => TextAlignment::CenterTop,
+            TextAlignment::CenterCenter => TextAlignment::CenterCenter,
+            TextAlignment::CenterBottom => TextAlignment::CenterBottom,
+        }
+    }
+}
+
+///A piece of a line with its own font and color, for text that mixes styles.
+#[derive(Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub font: Option<Font>,
+    pub font_color: Option<(f64, f64, f64)>,
+}
+impl TextRun {
+    ///A run in the text box's own font and color.
+    pub fn new<T: ToString>(text: T) -> Self {
+        Self {
+            text: text.to_string(),
+            font: None,
+            font_color: None,
+        }
+    }
+    pub fn with_font(mut self, font: Font) -> Self {
+        self.font = Some(font);
+        self
+    }
+    pub fn with_color(mut self, font_color: (f64, f64, f64)) -> Self {
+        self.font_color = Some(font_color);
+        self
+    }
+}
 
 pub struct TextBox {
     text: String,
@@ -44,6 +93,13 @@ pub struct TextBox {
     alignment: TextAlignment,
     compensate_for_font_decent: bool,
     group: Option<usize>,
+    line_spacing: f64,
+    padding: f64,
+    //filled in by a deferred build so {pages} can be replaced
+    total_pages: Option<usize>,
+    mirror_on_even_pages: bool,
+    runs: Vec<TextRun>,
+    wrap: bool,
 }
 
 impl TextBox {
@@ -63,6 +119,12 @@ impl TextBox {
             alignment: alignment.unwrap_or(TextAlignment::LeftBottom),
             compensate_for_font_decent: false,
             group,
+            line_spacing: 1.2,
+            padding: 0.0,
+            total_pages: None,
+            mirror_on_even_pages: false,
+            runs: vec![],
+            wrap: false,
         }
     }
     pub fn set_background(&mut self, back_ground: (f64, f64, f64)) {
@@ -71,26 +133,141 @@ impl TextBox {
     pub fn compensate_for_font_decent(&mut self, should_it: bool) {
         self.compensate_for_font_decent = should_it;
     }
-
-    fn adjust_for_font_text_alignment(
-        text: &String,
-        text_draw_info: &Rectangle,
-        font: &FontInfo,
+    ///Leading as a multiple of the font size, used between lines and for the content height.
+    pub fn set_line_spacing(&mut self, line_spacing: f64) {
+        self.line_spacing = line_spacing;
+    }
+    ///Pixels kept clear between the box edge and the text on every side.
+    pub fn set_padding(&mut self, padding: f64) {
+        self.padding = padding;
+    }
+    ///Swaps left and right alignment on even printed pages, for headers and footers with mirrored margins.
+    pub fn set_mirror_on_even_pages(&mut self, mirror_on_even_pages: bool) {
+        self.mirror_on_even_pages = mirror_on_even_pages;
+    }
+    ///Wraps lines too long for the box at spaces, and counts the wrapped lines in the content height.
+    ///Off by default, so each line is drawn as it is. Turn it on for auto-height placements.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+    ///Draws the text as one line of runs that each keep their own font and color, the font size
+    ///stays the text box's.
+    pub fn set_runs(&mut self, runs: Vec<TextRun>) {
+        self.text = runs.iter().map(|x| x.text.as_str()).collect();
+        self.runs = runs;
+    }
+    //page numbers start at 0, so odd ones print as even pages.
+    fn get_alignment(&self, page_number: usize) -> TextAlignment {
+        if self.mirror_on_even_pages && page_number % 2 == 1 {
+            self.alignment.mirror()
+        } else {
+            self.alignment.clone()
+        }
+    }
+    fn line_height(&self) -> f64 {
+        self.font.size * self.line_spacing
+    }
+    ///The text with {page} and, once known, {pages} filled in.
+    fn get_display_text(&self, page_number: usize) -> String {
+        let text = self.text.replace("{page}", &(page_number + 1).to_string());
+        match self.total_pages {
+            Some(total_pages) => text.replace("{pages}", &total_pages.to_string()),
+            None => text,
+        }
+    }
+    //the lines drawn on the page across width. Runs are always one line.
+    fn get_drawn_lines(&self, page_number: usize, width: f64) -> Vec<String> {
+        let text = self.get_display_text(page_number);
+        if !self.runs.is_empty() {
+            vec![text]
+        } else if self.wrap {
+            Self::wrap_text(&text, &self.font, width).split('\n').map(|x| x.to_string()).collect()
+        } else {
+            text.split('\n').map(|x| x.to_string()).collect()
+        }
+    }
+    //how far to move the line at index up from where the alignment puts a single line.
+    fn vertical_line_offset(
         alignment: &TextAlignment,
-        page_info: &PageSizeInfo,
-        compensate_decent: bool,
-    ) -> (f64, f64) {
+        index: usize,
+        line_count: usize,
+        line_height: f64,
+    ) -> f64 {
+        match alignment {
+            TextAlignment::LeftTop
+            | TextAlignment::LeftJustifyTop(_)
+            | TextAlignment::RightTop
+            | TextAlignment::RightJustifyTop(_)
+            | TextAlignment::CenterTop => -(index as f64) * line_height,
+            TextAlignment::LeftBottom
+            | TextAlignment::LeftJustifyBottom(_)
+            | TextAlignment::RightBottom
+            | TextAlignment::RightJustifyBottom(_)
+            | TextAlignment::CenterBottom => (line_count - 1 - index) as f64 * line_height,
+            TextAlignment::LeftCenter
+            | TextAlignment::LeftJustifyCenter(_)
+            | TextAlignment::RightCenter
+            | TextAlignment::RightJustifyCenter(_)
+            | TextAlignment::CenterCenter => {
+                ((line_count - 1) as f64 / 2.0 - index as f64) * line_height
+            }
+        }
+    }
+
+    ///The text with line breaks added so no line is wider than width pixels. Words that are wider
+    ///than a line on their own are broken between letters.
+    pub fn wrap_text(text: &str, font: &FontInfo, width: f64) -> String {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            for word in paragraph.split(' ') {
+                let joined = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+                if line.is_empty() || Self::get_line_width(&joined, font) <= width {
+                    line = joined;
+                } else {
+                    lines.push(line);
+                    line = word.to_string();
+                }
+                while line.chars().count() > 1 && Self::get_line_width(&line, font) > width {
+                    let mut fitting = line.chars().count() - 1;
+                    while fitting > 1
+                        && Self::get_line_width(&line.chars().take(fitting).collect::<String>(), font) > width
+                    {
+                        fitting -= 1;
+                    }
+                    lines.push(line.chars().take(fitting).collect());
+                    line = line.chars().skip(fitting).collect();
+                }
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    fn get_line_width(text: &str, font: &FontInfo) -> f64 {
         let widths = &font_sizes::GLYPH_WIDTHS[&font.font];
 
         //   &font_sizes::GLYPH_WIDTHS.iter();
 
-        let line_width = text
-            .chars()
+        text.chars()
             .filter(|c| *c != '\n')
             .map(|c| *widths.get(&c).unwrap_or(&1.0))
             .sum::<f64>()
-            * font.size;
+            * font.size
+    }
 
+    fn adjust_for_font_text_alignment(
+        line_width: f64,
+        text_draw_info: &Rectangle,
+        font: &FontInfo,
+        alignment: &TextAlignment,
+        page_info: &PageSizeInfo,
+        compensate_decent: bool,
+    ) -> (f64, f64) {
         let up_to_fit_in_box = match compensate_decent {
             true => {
                 let decent = &font_sizes::FONT_DESCENT[&font.font];
@@ -159,6 +336,73 @@ impl TextBox {
         }
     }
 }
+impl TextBox {
+    //the runs' line, aligned as a whole, changing font and color between runs.
+    fn draw_runs<T: DrawInfoReq>(
+        &self,
+        draw_to: &mut T,
+        placement_info: &PlacementInfo,
+        text_area: &Rectangle,
+        alignment: &TextAlignment,
+    ) {
+        let run_fonts: Vec<FontInfo> = self
+            .runs
+            .iter()
+            .map(|run| {
+                FontInfo::new_colored(
+                    self.font.size,
+                    run.font.clone().unwrap_or_else(|| self.font.font.clone()),
+                    run.font_color.unwrap_or(self.font.font_color),
+                )
+            })
+            .collect();
+        let run_widths: Vec<f64> = self
+            .runs
+            .iter()
+            .zip(run_fonts.iter())
+            .map(|(run, font)| Self::get_line_width(&run.text, font))
+            .collect();
+        let (start_x, start_y) = Self::adjust_for_font_text_alignment(
+            run_widths.iter().sum(),
+            text_area,
+            &self.font,
+            alignment,
+            &placement_info.page_size_info,
+            false,
+        );
+        let mut run_x = start_x;
+        let mut last_x = 0.0;
+        let line_y = start_y + text_area.y;
+        let runs = self.runs.iter().zip(run_fonts.iter()).zip(run_widths);
+        for (index, ((run, font), width)) in runs.enumerate() {
+            let fnt = font_sizes::CROSS_FONT_PDF[&font.font].clone();
+            draw_to.insert_into_page(placement_info.page_number, Operation::new(
+                "Tf",
+                vec![fnt.into(), font.size.into()],
+            ));
+            draw_to.insert_into_page(placement_info.page_number, Operation::new(
+                "rg",
+                vec![
+                    font.font_color.0.into(),
+                    font.font_color.1.into(),
+                    font.font_color.2.into(),
+                ],
+            ));
+            //only the first run moves down to the line
+            let move_y = if index == 0 { line_y } else { 0.0 };
+            draw_to.insert_into_page(placement_info.page_number, Operation::new(
+                "Td",
+                vec![(run_x - last_x).into(), move_y.into()],
+            ));
+            draw_to.insert_into_page(placement_info.page_number, Operation::new(
+                "Tj",
+                vec![Object::string_literal(run.text.clone())],
+            ));
+            last_x = run_x;
+            run_x += width;
+        }
+    }
+}
 impl Default for TextBox {
     fn default() -> Self {
         Self {
@@ -173,6 +417,12 @@ impl Default for TextBox {
             alignment: TextAlignment::LeftTop,
             compensate_for_font_decent: false,
             group: None,
+            line_spacing: 1.2,
+            padding: 0.0,
+            total_pages: None,
+            mirror_on_even_pages: false,
+            runs: vec![],
+            wrap: false,
         }
     }
 }
@@ -193,15 +443,6 @@ impl ContainerTrait for TextBox {
             .unwrap_or(outer_rec)
             .get_pdf_version(placement_info.page_size_info.clone());
 
-        let (start_x, start_y) = Self::adjust_for_font_text_alignment(
-            &self.text,
-            &text_draw_info,
-            &self.font,
-            &self.alignment,
-            &placement_info.page_size_info,
-            false,
-        );
-
         draw_to.insert_into_page(placement_info.page_number, Operation::new("q", vec![]));
         draw_to.insert_into_page(placement_info.page_number, Operation::new("re", text_draw_info.clone().into()));
         draw_to.insert_into_page(placement_info.page_number, Operation::new("W", vec![]));
@@ -255,6 +496,15 @@ impl ContainerTrait for TextBox {
             ],
         ));
 
+        let text_area = Rectangle::new(
+            text_draw_info.x + self.padding,
+            text_draw_info.y + self.padding,
+            text_draw_info.width - self.padding * 2.0,
+            text_draw_info.height - self.padding * 2.0,
+        );
+        let lines = self.get_drawn_lines(placement_info.page_number, text_area.width);
+        let line_count = lines.len();
+
         draw_to.insert_into_page(placement_info.page_number, Operation::new("BT", vec![]));
 
         //get F1 or whatever for font
@@ -264,17 +514,45 @@ impl ContainerTrait for TextBox {
             "Tf",
             vec![fnt.into(), self.font.size.into()],
         ));
-        draw_to.insert_into_page(placement_info.page_number, Operation::new(
-            "Td",
-            vec![
-                start_x.into(),                      //placement_info.left_pixel_x.into(),
-                (start_y + text_draw_info.y).into(), //(real_height + font_break.1 * placement_info.dpi).into(),
-            ],
-        ));
-        draw_to.insert_into_page(placement_info.page_number, Operation::new(
-            "Tj",
-            vec![Object::string_literal(self.text.clone())],
-        ));
+        let alignment = self.get_alignment(placement_info.page_number);
+        if !self.runs.is_empty() {
+            self.draw_runs(draw_to, &placement_info, &text_area, &alignment);
+        } else {
+            //Td is relative to the start of the previous line so track where we are.
+            let (mut last_x, mut last_y) = (0.0, 0.0);
+            for (index, line) in lines.into_iter().enumerate() {
+                let (start_x, start_y) = Self::adjust_for_font_text_alignment(
+                    Self::get_line_width(&line, &self.font),
+                    &text_area,
+                    &self.font,
+                    &alignment,
+                    &placement_info.page_size_info,
+                    false,
+                );
+                let line_x = start_x;
+                let line_y = start_y
+                    + text_area.y
+                    + Self::vertical_line_offset(
+                        &alignment,
+                        index,
+                        line_count,
+                        self.line_height(),
+                    );
+                draw_to.insert_into_page(placement_info.page_number, Operation::new(
+                    "Td",
+                    vec![
+                        (line_x - last_x).into(), //placement_info.left_pixel_x.into(),
+                        (line_y - last_y).into(), //(real_height + font_break.1 * placement_info.dpi).into(),
+                    ],
+                ));
+                draw_to.insert_into_page(placement_info.page_number, Operation::new(
+                    "Tj",
+                    vec![Object::string_literal(line)],
+                ));
+                last_x = line_x;
+                last_y = line_y;
+            }
+        }
         draw_to.insert_into_page(placement_info.page_number, Operation::new("ET", vec![]));
         /*
         {
@@ -330,4 +608,94 @@ impl ContainerTrait for TextBox {
     fn get_group(&self) -> Option<usize> {
         self.group
     }
+
+    fn get_content_height(&self, width_pixels: f64, _page_size_info: &PageSizeInfo) -> Option<f64> {
+        //the page isn't known yet, the last one has the widest {page}
+        let page_number = self.total_pages.map_or(0, |x| x.saturating_sub(1));
+        let line_count = self.get_drawn_lines(page_number, width_pixels - self.padding * 2.0).len();
+        Some(line_count as f64 * self.line_height() + self.padding * 2.0)
+    }
+
+    fn on_layout(&mut self, layout: &LayoutContext) {
+        self.total_pages = Some(layout.total_pages);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::test_helpers::Pages;
+
+    fn page() -> PageSizeInfo {
+        PageSizeInfo::new(612.0, 792.0, 72.0, 36.0, 36.0)
+    }
+
+    #[test]
+    fn content_height_wraps_to_width() {
+        let mut text_box = TextBox::new(
+            "one two three four five six",
+            FontInfo::new(10.0, Font::Helvetica),
+            None,
+            None,
+            None,
+            None,
+        );
+        text_box.set_wrap(true);
+        let one_line = text_box.get_content_height(1000.0, &page()).unwrap();
+        let wrapped = text_box.get_content_height(40.0, &page()).unwrap();
+        assert!((one_line - 12.0).abs() < 1e-9);
+        assert!(wrapped > one_line * 2.0);
+    }
+
+    #[test]
+    fn content_height_without_wrap_keeps_lines() {
+        let text_box = TextBox::new(
+            "one two three four five six\nseven",
+            FontInfo::new(10.0, Font::Helvetica),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!((text_box.get_content_height(40.0, &page()).unwrap() - 24.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn content_height_measures_the_text_that_is_drawn() {
+        let font = FontInfo::new(10.0, Font::Helvetica);
+        let mut text_box = TextBox::new("Page {page} of {pages}", font.clone(), None, None, None, None);
+        text_box.set_wrap(true);
+        text_box.on_layout(&LayoutContext {
+            total_pages: 3,
+            ..Default::default()
+        });
+        let width = TextBox::get_line_width("Page 3 of 3", &font) + 1.0;
+        assert!((text_box.get_content_height(width, &page()).unwrap() - 12.0).abs() < 1e-9);
+        text_box.set_runs(vec![TextRun::new("first\nsecond")]);
+        assert!((text_box.get_content_height(1000.0, &page()).unwrap() - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn runs_are_drawn_once_whatever_the_line_count() {
+        let mut text_box = TextBox::new(
+            "first\nsecond\nthird",
+            FontInfo::new(10.0, Font::Helvetica),
+            None,
+            None,
+            None,
+            None,
+        );
+        text_box.set_runs(vec![TextRun::new("bold").with_font(Font::HelveticaBold), TextRun::new(" plain")]);
+        let placement_info = PlacementInfo {
+            rec: Rectangle::new(72.0, 72.0, 300.0, 100.0),
+            draw_height_left_on_page: 600.0,
+            page_size_info: page(),
+            percent_range: 0.0..100.0,
+            ..Default::default()
+        };
+        let mut draw_to = Pages::default();
+        text_box.on_draw(placement_info, &mut draw_to, &None);
+        let shown = draw_to.pages[0].iter().filter(|x| x.operator == "Tj").count();
+        assert_eq!(shown, 2);
+    }
 }
@@ -0,0 +1,132 @@
+use crate::container_objects::text_box::TextRun;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+///A calendar date, shown as month/day/year.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Self {
+        Self { year, month, day }
+    }
+}
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.month, self.day, self.year)
+    }
+}
+
+///What a ListBox cell holds. Numbers stay numbers so the column's type formats them as they are.
+#[derive(Clone, PartialEq, Default)]
+pub enum CellValue {
+    #[default]
+    Empty,
+    Text(String),
+    Integer(i64),
+    Decimal(f64),
+    Date(Date),
+    Bool(bool),
+    RichText(Vec<TextRun>),
+}
+impl CellValue {
+    ///The value as a number. Text is read as one, for rows that are still built from strings.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CellValue::Integer(value) => Some(*value as f64),
+            CellValue::Decimal(value) => Some(*value),
+            CellValue::Text(text) => text.parse::<f64>().ok(),
+            _ => None,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        match self {
+            CellValue::Empty => true,
+            CellValue::Text(text) => text.is_empty(),
+            CellValue::RichText(runs) => runs.iter().all(|x| x.text.is_empty()),
+            _ => false,
+        }
+    }
+}
+impl Display for CellValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Empty => Ok(()),
+            CellValue::Text(text) => write!(f, "{}", text),
+            CellValue::Integer(value) => write!(f, "{}", value),
+            CellValue::Decimal(value) => write!(f, "{}", value),
+            CellValue::Date(date) => write!(f, "{}", date),
+            CellValue::Bool(true) => write!(f, "Yes"),
+            CellValue::Bool(false) => write!(f, "No"),
+            CellValue::RichText(runs) => runs.iter().try_for_each(|x| write!(f, "{}", x.text)),
+        }
+    }
+}
+impl From<String> for CellValue {
+    fn from(text: String) -> Self {
+        CellValue::Text(text)
+    }
+}
+impl From<&str> for CellValue {
+    fn from(text: &str) -> Self {
+        CellValue::Text(text.to_owned())
+    }
+}
+impl From<&String> for CellValue {
+    fn from(text: &String) -> Self {
+        CellValue::Text(text.clone())
+    }
+}
+impl From<i64> for CellValue {
+    fn from(value: i64) -> Self {
+        CellValue::Integer(value)
+    }
+}
+impl From<i32> for CellValue {
+    fn from(value: i32) -> Self {
+        CellValue::Integer(value.into())
+    }
+}
+impl From<u32> for CellValue {
+    fn from(value: u32) -> Self {
+        CellValue::Integer(value.into())
+    }
+}
+impl From<usize> for CellValue {
+    fn from(value: usize) -> Self {
+        CellValue::Integer(value as i64)
+    }
+}
+impl From<f64> for CellValue {
+    fn from(value: f64) -> Self {
+        CellValue::Decimal(value)
+    }
+}
+impl From<f32> for CellValue {
+    fn from(value: f32) -> Self {
+        CellValue::Decimal(value.into())
+    }
+}
+impl From<bool> for CellValue {
+    fn from(value: bool) -> Self {
+        CellValue::Bool(value)
+    }
+}
+impl From<Date> for CellValue {
+    fn from(date: Date) -> Self {
+        CellValue::Date(date)
+    }
+}
+impl From<Vec<TextRun>> for CellValue {
+    fn from(runs: Vec<TextRun>) -> Self {
+        CellValue::RichText(runs)
+    }
+}
+impl<T: Into<CellValue>> From<Option<T>> for CellValue {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or_default()
+    }
+}
@@ -1 +1,2 @@
+pub mod cell_value;
 pub mod currency;
\ No newline at end of file
@@ -3,4 +3,7 @@ pub mod container_objects;
 pub mod format_objects;
 pub mod font;
 #[macro_use]
-extern crate lazy_static;
\ No newline at end of file
+extern crate lazy_static;
+//lets #[derive(ListRow)], which names the crate, be used in the crate's own tests
+#[cfg(test)]
+extern crate self as backfat;
@@ -7,13 +7,27 @@ use backfat::font::font_sizes::{Font, create_font_recource_id};
 use backfat::font::font_info::{FontInfo};
 use backfat::container::rectangle::Border;
 use std::cell::RefCell;
-use backfat::container_objects::list_box::{ListBoxBorder, TypeOfItem, ListBox, RowData, RowDataTypes};
+use backfat::container_objects::list_box::{Aggregate, ListBoxBorder, ListBox, ListRow, RowData, RowDataTypes};
 use backfat::container_objects::lines::{draw_rectangle, draw_vertical_line};
-use backfat::container::placement_info::PlacementInfo;
+use backfat::format_objects::cell_value::{CellValue, Date};
+use backfat::container::placement_info::{EdgeAlignment, PlacementInfo, RelativePlacement, Relation};
 
 use rand::Rng;
 use backfat::container::container_trait::DrawInfoReq;
 
+//a row of the list box, its columns come from the attributes
+#[derive(ListRow)]
+struct Invoice {
+    #[list_row(width = 25.0, align = "CenterTop")]
+    date: Date,
+    #[list_row(width = 25.0, align = "CenterCenter")]
+    invoice_number: Option<String>,
+    #[list_row(width = 25.0, currency = 2, align = "CenterCenter")]
+    invoice_amount: f64,
+    #[list_row(width = 25.0, currency = 2, align = "RightBottom")]
+    remaining_budget: u32,
+}
+
 struct PdfDox {
     manager: Manager,
 }
@@ -42,6 +56,20 @@ impl DrawInfoReq for PdfDrawInfo {
     fn insert_into_page(&mut self, page_num: usize, operation: Operation) {
         self.pdf[page_num].push(operation);
     }
+
+    fn page_operation_len(&self, page_num: usize) -> usize {
+        self.pdf.get(page_num).map_or(0, |x| x.len())
+    }
+
+    fn truncate_page(&mut self, page_num: usize, len: usize) {
+        if let Some(page) = self.pdf.get_mut(page_num) {
+            page.truncate(len);
+        }
+    }
+
+    fn truncate_pages(&mut self, page_cnt: usize) {
+        self.pdf.truncate(page_cnt);
+    }
 }
 fn mimic_report() {
     let borders: Option<RefCell<Vec<Border>>> = Some(RefCell::new(Vec::new()));
@@ -63,9 +91,8 @@ fn mimic_report() {
     }
     {
         let mut txt = TextBox::new("Stuff", FontInfo::new(13.0, Font::Helvetica), Some(TextAlignment::CenterCenter), Some(BorderStyle::Single(1.0)), Some((0.9, 0.9, 0.9)), None);
-        let mut placement_handle = dox.manager.get_placement_handle(50..92, false);
-        placement_handle.set_pixel_height(0.25 * 72.0);
-        placement_handle.draw(&mut txt, &mut pdf_draw, &borders);
+        txt.set_wrap(true);
+        dox.manager.place_auto_height(50..92, Some(0.25 * 72.0), None, &mut pdf_draw, &mut txt, &borders);
     }
     {
         let mut txt = TextBox::new("", FontInfo::new(12.0, Font::Helvetica), Some(TextAlignment::CenterCenter), None, None, None);
@@ -75,15 +102,13 @@ fn mimic_report() {
     }
     {
         let mut txt = TextBox::new("Enter Month", FontInfo::new(11.0, Font::Helvetica), Some(TextAlignment::CenterCenter), None, Some((0.9,0.9,0.9)), None);
-        let mut placement_handle = dox.manager.get_placement_handle(12..24, false);
-        placement_handle.set_pixel_height(0.25 * 72.0);
-        placement_handle.draw(&mut txt, &mut pdf_draw, &borders);
+        txt.set_wrap(true);
+        dox.manager.place_auto_height(12..24, Some(0.25 * 72.0), None, &mut pdf_draw, &mut txt, &borders);
     }
     {
         let mut txt = TextBox::new("Enter Year", FontInfo::new(12.0, Font::Helvetica), Some(TextAlignment::CenterCenter), None, None, None);
-        let mut placement_handle = dox.manager.get_placement_handle(31..41, false);
-        placement_handle.set_pixel_height(0.25 * 72.0);
-        placement_handle.draw(&mut txt, &mut pdf_draw, &borders);
+        txt.set_wrap(true);
+        dox.manager.place_auto_height(31..41, Some(0.25 * 72.0), None, &mut pdf_draw, &mut txt, &borders);
     }
     {
         let mut txt = TextBox::new("May",FontInfo::new(14.0, Font::Helvetica), Some(TextAlignment::CenterBottom), Some(BorderStyle::Single(1.0)), Some((0.5, 0.7, 0.9)), None );
@@ -100,22 +125,23 @@ fn mimic_report() {
     }
     let mut test_data = Vec::new();
     for i in 0..177 {
-        let row_data = vec!["5/12/2020".to_owned(),"".to_owned(),"2032.90".to_owned(),format!( "{}", i).to_owned()];
-        test_data.push(RowData::new(row_data, RowDataTypes::default()));
+        let invoice = Invoice {
+            date: Date::new(2020, 5 + i / 60, 12),
+            invoice_number: None,
+            invoice_amount: 2032.90,
+            remaining_budget: i,
+        };
+        test_data.push(invoice.to_row());
     }
     //doing a single row
-    test_data.insert(4, RowData::new(vec!["Bacon".into()], RowDataTypes::SingleNoBorderWithColor((0.0,1.0,1.0), TextAlignment::CenterCenter)));
-    test_data.push(RowData::new(vec!["Ham".into()], RowDataTypes::SingleWithColor((0.0,1.0,1.0), TextAlignment::RightBottom)) );
-    test_data.insert(42, RowData::new(vec!["Honey".into()], RowDataTypes::SingleNoBorderWithColor((1.0,0.0,0.2), TextAlignment::CenterCenter)));
+    test_data.push(RowData::new(vec!["Ham"], RowDataTypes::SingleWithColor((0.0,1.0,1.0), TextAlignment::RightBottom)) );
     //this gets the top most place on the page at that page range.
     let mut placement_handle = dox.manager.get_placement_handle(50..90, false);
 
-    let col_size: Vec<usize> = vec![10, 10, 10, 10];//vec![9, 10, 11, 12];
-    let header_data: Vec<String> = vec!["Date".to_owned(), "Invoice Number".to_owned(), "Invoice Amount".to_owned(), "Remaining Budget".to_owned() ];
-    let header = RowData::new(header_data, RowDataTypes::default());
+    let header = Invoice::header();
     //create list box
     let mut list_box = ListBox::new(&test_data,
-                                    col_size,
+                                    Invoice::column_widths(40.0),
                                     Some(&header),
                                     &mut dox.manager,
                                     FontInfo::new(5.0, Font::Helvetica),
@@ -133,16 +159,37 @@ fn mimic_report() {
     //if ignored the sizes will all be the same based on the ListBox list_item_font
     list_box.set_all_row_sizes(sizes);
 
-    //if wanting something besides strings set here
-    list_box.set_row_types(vec![TypeOfItem::String,TypeOfItem::String,TypeOfItem::Currency(2),TypeOfItem::Currency(2)]);
-    //align list box items columns
-    list_box.set_item_column_alignments( vec![TextAlignment::CenterTop, TextAlignment::CenterCenter, TextAlignment::CenterCenter, TextAlignment::RightBottom, TextAlignment::CenterCenter] );
+    //column types and item alignments from the Invoice attributes
+    list_box.set_columns_from::<Invoice>();
     //align list box header columns
     list_box.set_header_column_alignments( vec![TextAlignment::LeftJustifyTop(0.05);4] );
     //if false will appear below header row
     list_box.header_has_border(false);
+    //totals row after the data, styled apart from the header
+    list_box.set_footer(vec![Aggregate::Label("Total".into()), Aggregate::None, Aggregate::Sum, Aggregate::Count]);
+    list_box.set_footer_style(FontInfo::new(6.0, Font::HelveticaBold), (0.8, 0.8, 0.8));
+    //running totals where the table breaks across pages
+    list_box.set_carry_forward(vec![2], "Carried forward", "Brought forward");
+    //a header row for each month with its subtotal under it
+    list_box.set_group_by(|row| match &row.data[0] {
+        CellValue::Date(date) => date.month.to_string(),
+        other => other.to_string(),
+    });
+    list_box.set_group_header_style((0.0, 0.0, 0.6), TextAlignment::CenterCenter);
+    list_box.set_group_footer(vec![Aggregate::Label("Subtotal {group}".into()), Aggregate::None, Aggregate::Sum, Aggregate::Count]);
+    list_box.set_restart_zebra_per_group(true);
+    //lets the note below find the amount column
+    list_box.name_column(2, "amount");
 
     placement_handle.draw( &mut list_box, &mut pdf_draw, &borders);
+    //gives the manager back
+    drop(list_box);
+
+    {
+        let mut txt = TextBox::new("Amounts in USD", FontInfo::new(6.0, Font::Helvetica), Some(TextAlignment::RightTop), None, None, None);
+        let relative = RelativePlacement::new(Relation::Below, EdgeAlignment::End).with_offset(0.0, 2.0);
+        dox.manager.place_relative("amount", relative, (1.0 * 72.0, 0.15 * 72.0), &mut pdf_draw, &mut txt, &borders);
+    }
 
     //drawing a border around the group
     for group_rec in dox.manager.get_groups() {
@@ -151,7 +198,7 @@ fn mimic_report() {
             let mut pl: PlacementInfo = PlacementInfo::default();
             pl.page_number = page_index;
             pl.rec = group_rec.1[page_index].clone();
-            pl.page_size_info = dox.manager.get_page_info();
+            pl.page_size_info = dox.manager.get_page_size_info(page_index);
 
             //println!("{}",pl.page_size_info);
         /*        draw_rectangle(&mut pdf_draw,
@@ -206,10 +253,12 @@ fn mimic_report() {
         };
         let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
 
+        let page_size_info = dox.manager.get_page_size_info(page);
         let page_id = doc.add_object(dictionary! {
             "Type" => "Page",
             "Parent" => pages_id,
             "Contents" => content_id,
+            "MediaBox" => vec![0.into(), 0.into(), page_size_info.page_width_pixels.into(), page_size_info.page_height_pixels.into()],
             });
         v.push( page_id.into() )
     };
@@ -219,7 +268,6 @@ fn mimic_report() {
 		"Kids" => v,
 		"Count" => page_count,
 		"Resources" => resources_id,
-		"MediaBox" => vec![0.into(), 0.into(), (dox.manager.get_page_pixel_dims().0).into(), (dox.manager.get_page_pixel_dims().1).into()],
 	};
     doc.objects.insert(pages_id, Object::Dictionary(pages));
     let catalog_id = doc.add_object(dictionary! {